@@ -0,0 +1,44 @@
+// Compares sharing a preloaded 4096x4096 transparency mask through Rc::clone
+// (what TextureManager::get_preload does) with deep copying it (what it used to do).
+// Run with: cargo bench --bench mask_clone
+use std::hint::black_box;
+use std::rc::Rc;
+use std::time::Instant;
+
+// Only part of the module is used here, and its unit tests are not run from a bench
+#[allow(dead_code, unused_imports)]
+#[path = "../src/modules/transparency_mask.rs"]
+mod transparency_mask;
+
+use transparency_mask::{MaskMode, MaskSettings, TransparencyMask};
+
+const SIZE: usize = 4096;
+
+// Time f over the given number of runs and print the average
+fn bench(name: &str, runs: u32, mut f: impl FnMut()) {
+    f(); // Warm up
+    let start = Instant::now();
+    for _ in 0..runs {
+        f();
+    }
+    let per_run = start.elapsed().as_secs_f64() / runs as f64;
+    println!("{name:<32} {:>12.1} ns", per_run * 1e9);
+}
+
+fn compare(label: &str, mask: TransparencyMask) {
+    let shared = Rc::new(mask);
+    bench(&format!("{label} Rc::clone"), 1_000_000, || {
+        black_box(Rc::clone(black_box(&shared)));
+    });
+    bench(&format!("{label} deep copy"), 20, || {
+        black_box(TransparencyMask::clone(black_box(&shared)));
+    });
+}
+
+fn main() {
+    println!("Cloning a {SIZE}x{SIZE} TransparencyMask, average per clone:");
+    compare("bitmask", TransparencyMask::filled(SIZE, SIZE));
+    let pixels = vec![255u8; SIZE * SIZE * 4];
+    let settings = MaskSettings { alpha_threshold: 0, mode: MaskMode::Alpha };
+    compare("alpha", TransparencyMask::from_rgba_with(&pixels, SIZE, SIZE, settings));
+}
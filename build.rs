@@ -0,0 +1,48 @@
+// Build script: lists every image in the assets folder so the embedded_assets!() macro
+// can include them in the binary (see src/modules/image_preload.rs)
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// File types the image loader can decode
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "tga", "gif"];
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let assets_dir = Path::new(&manifest_dir).join("assets");
+    println!("cargo:rerun-if-changed=assets");
+
+    let mut files = Vec::new();
+    collect_images(&assets_dir, "assets", &mut files);
+    files.sort();
+
+    let mut code = String::from("&[\n");
+    for name in &files {
+        let full_path = Path::new(&manifest_dir).join(name);
+        code.push_str(&format!("    ({:?}, include_bytes!({:?}) as &[u8]),\n", name, full_path.display().to_string()));
+    }
+    code.push_str("]\n");
+    fs::write(Path::new(&out_dir).join("embedded_assets.rs"), code).unwrap();
+}
+
+// Walk a folder, recording image files as "assets/sub/name.png" style paths
+fn collect_images(dir: &Path, prefix: &str, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            println!("cargo:rerun-if-changed={}", name);
+            collect_images(&path, &name, files);
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        {
+            files.push(name);
+        }
+    }
+}
@@ -8,6 +8,12 @@ mod modules {
     pub mod still_image;
     pub mod label;
     pub mod txt_buttons;
+    pub mod transparency_mask;
+    pub mod loading_screen;
+    pub mod texture_atlas;
+    pub mod sprite_sheet;
+    pub mod animated_image;
+    pub mod image_filters;
 }
 
 use macroquad::prelude::*;
@@ -34,8 +40,12 @@ async fn main() {
     // Create and initialize the texture manager
     let mut texture_manager = TextureManager::new();
 
-    // Preload all textures at startup
-    texture_manager.preload_all(&["assets/image1.png", "assets/image2.png"]).await;
+    // Preload all textures listed in the manifest at startup
+    // New images can be added to assets/manifest.json without changing any code
+    texture_manager.load_manifest("assets/manifest.json").await.unwrap();
+
+    // Keep a handle for image 2 so the button doesn't need a lookup each click
+    let image2 = texture_manager.get_handle("image2").unwrap();
 
     // Create a single image object
     let mut img = StillImage::new(
@@ -128,7 +138,8 @@ async fn main() {
         // Handle image1 button
         if btn_image1.click() {
             // Using the safer pattern matching approach
-            if let Some(preloaded) = texture_manager.get_preload("assets/image1.png") {
+            // "image1" is the alias given to assets/image1.png in the manifest
+            if let Some(preloaded) = texture_manager.get_preload("image1") {
                 img.set_preload(preloaded);
 
             } else {
@@ -141,10 +152,10 @@ async fn main() {
 
         // Handle image2 button
         if btn_image2.click() {
-            // Direct unwrap approach - simpler but will panic if image is missing
-            // This approach assumes the image was preloaded and is definitely available
-            img.set_preload(texture_manager.get_preload("assets/image2.png").unwrap());
-        
+            // Handle approach - no path lookup, and no unwrap needed
+            // set_handle returns false if the texture was unloaded since
+            img.set_handle(&texture_manager, image2);
+
         }
 
         // Handle clear button
@@ -0,0 +1,565 @@
+/*
+Made by: image_test contributors
+Date: 2026-10-18
+Program Details: Frame-based animation on top of StillImage
+
+To import you need:
+In the mod modules section add:
+    pub mod animated_image;
+    pub mod sprite_sheet;
+    pub mod still_image;
+    pub mod image_preload;
+    pub mod transparency_mask;
+
+Then add the following with the use commands:
+use crate::modules::animated_image::{AnimatedImage, AnimationEvent, PlayMode};
+
+Usage examples:
+1. Animate the frames of a sprite sheet at 12 frames per second:
+    let sheet = SpriteSheet::from_grid(texture_manager.get_preload("assets/hero_run.png").unwrap(), 32.0, 48.0);
+    let img = StillImage::new("", 64.0, 96.0, 200.0, 60.0, true, 1.0).await;
+    let mut hero = AnimatedImage::from_sheet(img, &sheet, 1.0 / 12.0);
+
+2. Or use separate preloaded textures as frames:
+    let handles = texture_manager.preload_all(&["assets/fire1.png", "assets/fire2.png", "assets/fire3.png"]).await;
+    let mut fire = AnimatedImage::from_handles(img, &texture_manager, &handles, 0.1);
+
+   Or play an animated GIF or APNG with the delays stored in the file:
+    texture_manager.preload_animation("assets/fire.gif").await;
+    let mut fire = AnimatedImage::from_frames(img, texture_manager.get_animation("assets/fire.gif").unwrap());
+
+3. Give single frames their own length and pick how the animation repeats:
+    hero.set_frame_duration(0, 0.5);   // Hold the first frame for half a second
+    hero.set_mode(PlayMode::PingPong); // Loop (default), PingPong or Once
+
+4. Update and draw in your game loop, reacting to events if you want:
+    for event in hero.update() {
+        match event {
+            AnimationEvent::FrameChanged(frame) => println!("Now showing frame {}", frame),
+            AnimationEvent::Looped => {}
+            AnimationEvent::Finished => println!("Done"),
+        }
+    }
+    hero.draw();
+
+5. Playback controls:
+    hero.pause();
+    hero.play();          // Restarts from the first frame if a Once animation had finished
+    hero.stop();          // Pause and go back to the first frame
+    hero.seek(3);         // Jump to a frame
+    hero.set_speed(2.0);  // Play twice as fast
+
+6. Following reloaded files (TextureManager::reload or hot reload):
+    hero.refresh(&texture_manager); // Once per frame, only frames added with from_handles update
+    // Sprite sheets and from_frames keep the textures they were built with;
+    // build the animation again (e.g. from get_animation) to show a reloaded GIF
+
+Collision always uses the mask of the frame being shown:
+    if hero.collides_with(wall.image()) { ... }
+Use image() and image_mut() for anything else StillImage can do (zoom, stretch, ...).
+*/
+use macroquad::prelude::*;
+use crate::modules::image_preload::{PreloadedTexture, TextureHandle, TextureManager};
+use crate::modules::sprite_sheet::SpriteSheet;
+use crate::modules::still_image::{Collision, StillImage};
+
+// Shortest frame duration allowed, so zero-length frames can't stall update()
+const MIN_FRAME_DURATION: f32 = 0.001;
+
+// How the animation continues after the last frame
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlayMode {
+    #[default]
+    Loop,     // Jump back to the first frame
+    PingPong, // Play backwards to the first frame, then forwards again
+    Once,     // Stop on the last frame
+}
+
+// Things that happened during one update
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationEvent {
+    FrameChanged(usize), // A new frame is showing
+    Looped,              // The animation went back to the start (Loop and PingPong)
+    Finished,            // A Once animation reached the end of its last frame
+}
+
+// One frame: what to show and for how long
+struct AnimationFrame {
+    preloaded: PreloadedTexture,
+    source: Option<Rect>,                  // Sprite sheet cell, None for the whole texture
+    duration: f32,                         // Seconds
+    handle: Option<(TextureHandle, u32)>, // Where the frame came from and its revision, for refresh
+}
+
+// Which frame is showing and how long it has been up
+// Kept apart from the textures so the stepping works (and can be tested) without a GPU
+#[derive(Debug, Clone, PartialEq)]
+struct Playhead {
+    current: usize,
+    elapsed: f32, // Seconds spent on the current frame
+    mode: PlayMode,
+    playing: bool,
+    finished: bool,
+    forward: bool, // Ping-pong direction
+    speed: f32,    // Playback rate multiplier
+}
+
+impl Playhead {
+    fn new() -> Self {
+        Self {
+            current: 0,
+            elapsed: 0.0,
+            mode: PlayMode::default(),
+            playing: true,
+            finished: false,
+            forward: true,
+            speed: 1.0,
+        }
+    }
+
+    // Go to a frame with its full duration ahead, playing forwards
+    fn seek(&mut self, index: usize) {
+        self.current = index;
+        self.elapsed = 0.0;
+        self.finished = false;
+        self.forward = true;
+    }
+
+    // Advance by dt seconds through frames whose durations come from duration(index)
+    // Returns what happened, in order
+    fn advance(&mut self, frame_count: usize, duration: impl Fn(usize) -> f32, dt: f32) -> Vec<AnimationEvent> {
+        let mut events = Vec::new();
+        if !self.playing || frame_count == 0 {
+            return events;
+        }
+        self.elapsed += dt * self.speed;
+        while self.elapsed >= duration(self.current) {
+            self.elapsed -= duration(self.current);
+            match self.next_frame(frame_count - 1) {
+                Some((next, looped)) => {
+                    if looped {
+                        events.push(AnimationEvent::Looped);
+                    }
+                    if next != self.current {
+                        self.current = next;
+                        events.push(AnimationEvent::FrameChanged(next));
+                    }
+                }
+                None => {
+                    self.elapsed = 0.0;
+                    self.playing = false;
+                    self.finished = true;
+                    events.push(AnimationEvent::Finished);
+                    break;
+                }
+            }
+        }
+        events
+    }
+
+    // The frame after the current one and whether moving there starts a new cycle
+    // None when a Once animation has run out of frames
+    fn next_frame(&mut self, last: usize) -> Option<(usize, bool)> {
+        match self.mode {
+            PlayMode::Loop if self.current >= last => Some((0, true)),
+            PlayMode::Loop => Some((self.current + 1, false)),
+            PlayMode::Once => (self.current < last).then_some((self.current + 1, false)),
+            PlayMode::PingPong if last == 0 => Some((0, true)),
+            PlayMode::PingPong => {
+                if self.forward && self.current >= last {
+                    self.forward = false;
+                } else if !self.forward && self.current == 0 {
+                    self.forward = true;
+                }
+                let next = if self.forward { self.current + 1 } else { self.current - 1 };
+                // A cycle ends when the animation is back on the first frame
+                Some((next, next == 0))
+            }
+        }
+    }
+}
+
+pub struct AnimatedImage {
+    image: StillImage,
+    frames: Vec<AnimationFrame>,
+    playhead: Playhead,
+}
+
+impl AnimatedImage {
+    // Wrap a StillImage with no frames yet; add them with add_frame or add_sprite
+    // Position, size, stretch and zoom come from the image
+    #[allow(unused)]
+    pub fn new(image: StillImage) -> Self {
+        Self {
+            image,
+            frames: Vec::new(),
+            playhead: Playhead::new(),
+        }
+    }
+
+    // Animate every frame of a sprite sheet, each shown for frame_duration seconds
+    #[allow(unused)]
+    pub fn from_sheet(image: StillImage, sheet: &SpriteSheet, frame_duration: f32) -> Self {
+        let mut animation = Self::new(image);
+        for index in 0..sheet.frame_count() {
+            animation.add_sprite(sheet, index, frame_duration);
+        }
+        animation
+    }
+
+    // Animate a list of TextureManager entries, each shown for frame_duration seconds
+    // Stale handles are skipped. Frames added this way follow reloads when refresh is called
+    #[allow(unused)]
+    pub fn from_handles(image: StillImage, texture_manager: &TextureManager, handles: &[TextureHandle], frame_duration: f32) -> Self {
+        let mut animation = Self::new(image);
+        for &handle in handles {
+            if let (Some(preloaded), Some(revision)) = (texture_manager.get(handle), texture_manager.revision(handle)) {
+                animation.push_frame(preloaded, None, frame_duration);
+                if let Some(frame) = animation.frames.last_mut() {
+                    frame.handle = Some((handle, revision));
+                }
+            }
+        }
+        animation
+    }
+
+    // Animate a list of textures with their own delays in seconds,
+    // for example TextureManager::get_animation for a GIF or APNG
+    #[allow(unused)]
+    pub fn from_frames(image: StillImage, frames: Vec<(PreloadedTexture, f32)>) -> Self {
+        let mut animation = Self::new(image);
+        for (preloaded, duration) in frames {
+            animation.add_frame(preloaded, duration);
+        }
+        animation
+    }
+
+    // Add a whole texture as the next frame
+    #[allow(unused)]
+    pub fn add_frame(&mut self, preloaded: PreloadedTexture, duration: f32) {
+        self.push_frame(preloaded, None, duration);
+    }
+
+    // Add one cell of a sprite sheet as the next frame
+    // Returns false if the sheet has no frame at that index
+    #[allow(unused)]
+    pub fn add_sprite(&mut self, sheet: &SpriteSheet, index: usize, duration: f32) -> bool {
+        match sheet.frame(index) {
+            Some(rect) => {
+                self.push_frame(sheet.preloaded(), Some(rect), duration);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn push_frame(&mut self, preloaded: PreloadedTexture, source: Option<Rect>, duration: f32) {
+        self.frames.push(AnimationFrame {
+            preloaded,
+            source,
+            duration: duration.max(MIN_FRAME_DURATION),
+            handle: None,
+        });
+        // Show the first frame as soon as there is one
+        if self.frames.len() == 1 {
+            self.show(0);
+        }
+    }
+
+    // Change how long one frame is shown, in seconds
+    #[allow(unused)]
+    pub fn set_frame_duration(&mut self, index: usize, duration: f32) {
+        if let Some(frame) = self.frames.get_mut(index) {
+            frame.duration = duration.max(MIN_FRAME_DURATION);
+        }
+    }
+
+    // Give every frame the same duration, in seconds
+    #[allow(unused)]
+    pub fn set_all_durations(&mut self, duration: f32) {
+        for frame in &mut self.frames {
+            frame.duration = duration.max(MIN_FRAME_DURATION);
+        }
+    }
+
+    #[allow(unused)]
+    pub fn set_mode(&mut self, mode: PlayMode) {
+        self.playhead.mode = mode;
+    }
+
+    #[allow(unused)]
+    pub fn get_mode(&self) -> PlayMode {
+        self.playhead.mode
+    }
+
+    // Playback rate, 1.0 is normal speed. Negative values are treated as 0
+    #[allow(unused)]
+    pub fn set_speed(&mut self, speed: f32) {
+        self.playhead.speed = speed.max(0.0);
+    }
+
+    // Start or resume playing. A finished Once animation starts over
+    #[allow(unused)]
+    pub fn play(&mut self) {
+        if self.playhead.finished {
+            self.seek(0);
+        }
+        self.playhead.playing = true;
+    }
+
+    #[allow(unused)]
+    pub fn pause(&mut self) {
+        self.playhead.playing = false;
+    }
+
+    // Pause and go back to the first frame
+    #[allow(unused)]
+    pub fn stop(&mut self) {
+        self.playhead.playing = false;
+        self.seek(0);
+    }
+
+    #[allow(unused)]
+    pub fn is_playing(&self) -> bool {
+        self.playhead.playing
+    }
+
+    // True once a Once animation has reached the end
+    #[allow(unused)]
+    pub fn is_finished(&self) -> bool {
+        self.playhead.finished
+    }
+
+    // Jump to a frame and start its full duration. Out of range indexes are ignored
+    #[allow(unused)]
+    pub fn seek(&mut self, index: usize) {
+        if index < self.frames.len() {
+            self.playhead.seek(index);
+            self.show(index);
+        }
+    }
+
+    #[allow(unused)]
+    pub fn current_frame(&self) -> usize {
+        self.playhead.current
+    }
+
+    #[allow(unused)]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Advance by this frame's time (get_frame_time) and return what happened
+    #[allow(unused)]
+    pub fn update(&mut self) -> Vec<AnimationEvent> {
+        self.advance(get_frame_time())
+    }
+
+    // Advance by dt seconds and return what happened, in order
+    // A long dt can step over several frames, each reported with its own event
+    pub fn advance(&mut self, dt: f32) -> Vec<AnimationEvent> {
+        let frames = &self.frames;
+        let events = self.playhead.advance(frames.len(), |index| frames[index].duration, dt);
+        if events.iter().any(|event| matches!(event, AnimationEvent::FrameChanged(_))) {
+            self.show(self.playhead.current);
+        }
+        events
+    }
+
+    // Put a frame's texture, mask and source rectangle on the image
+    fn show(&mut self, index: usize) {
+        let frame = &self.frames[index];
+        self.image.set_preload(frame.preloaded.clone());
+        if let Some(source) = frame.source {
+            self.image.set_source_rect(source);
+        }
+    }
+
+    // Pick up reloaded textures for frames added with from_handles
+    // Call once per frame when using TextureManager::reload or hot reload
+    // Frames added any other way keep the texture they were given
+    // Returns true if any frame changed
+    #[allow(unused)]
+    pub fn refresh(&mut self, texture_manager: &TextureManager) -> bool {
+        let mut changed = false;
+        for frame in &mut self.frames {
+            let Some((handle, revision)) = frame.handle else {
+                continue;
+            };
+            let Some(latest) = texture_manager.revision(handle).filter(|latest| *latest != revision) else {
+                continue;
+            };
+            if let Some(preloaded) = texture_manager.get(handle) {
+                frame.preloaded = preloaded;
+                frame.handle = Some((handle, latest));
+                changed = true;
+            }
+        }
+        if changed {
+            self.show(self.playhead.current);
+        }
+        changed
+    }
+
+    #[allow(unused)]
+    pub fn draw(&self) {
+        self.image.draw();
+    }
+
+    #[allow(unused)]
+    pub fn set_position(&mut self, pos: Vec2) {
+        self.image.set_position(pos);
+    }
+
+    #[allow(unused)]
+    pub fn pos(&self) -> Vec2 {
+        self.image.pos()
+    }
+
+    // Hit-test against the current frame's mask
+    #[allow(unused)]
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        self.image.contains_point(point)
+    }
+
+    // Pixel collision using the current frame's mask
+    #[allow(unused)]
+    pub fn collides_with(&self, other: &StillImage) -> bool {
+        self.image.collides_with(other)
+    }
+
+    #[allow(unused)]
+    pub fn collision_with(&self, other: &StillImage) -> Option<Collision> {
+        self.image.collision_with(other)
+    }
+
+    // The StillImage showing the current frame
+    #[allow(unused)]
+    pub fn image(&self) -> &StillImage {
+        &self.image
+    }
+
+    // Change position, size, zoom and other StillImage settings
+    // Setting a texture directly is overwritten at the next frame change
+    #[allow(unused)]
+    pub fn image_mut(&mut self) -> &mut StillImage {
+        &mut self.image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AnimationEvent::{FrameChanged, Finished, Looped};
+
+    // Step a playhead over frames that each last one second
+    fn step(playhead: &mut Playhead, frame_count: usize, dt: f32) -> Vec<AnimationEvent> {
+        playhead.advance(frame_count, |_| 1.0, dt)
+    }
+
+    fn playhead(mode: PlayMode) -> Playhead {
+        let mut playhead = Playhead::new();
+        playhead.mode = mode;
+        playhead
+    }
+
+    #[test]
+    fn loop_wraps_to_the_first_frame() {
+        let mut playhead = playhead(PlayMode::Loop);
+        assert_eq!(step(&mut playhead, 3, 0.5), vec![]);
+        assert_eq!(step(&mut playhead, 3, 0.5), vec![FrameChanged(1)]);
+        assert_eq!(step(&mut playhead, 3, 1.0), vec![FrameChanged(2)]);
+        // Looped comes before the change back to frame 0
+        assert_eq!(step(&mut playhead, 3, 1.0), vec![Looped, FrameChanged(0)]);
+        assert!(playhead.playing && !playhead.finished);
+    }
+
+    #[test]
+    fn single_frame_loop_only_reports_looped() {
+        let mut playhead = playhead(PlayMode::Loop);
+        assert_eq!(step(&mut playhead, 1, 1.0), vec![Looped]);
+        let mut playhead = self::playhead(PlayMode::PingPong);
+        assert_eq!(step(&mut playhead, 1, 1.0), vec![Looped]);
+    }
+
+    #[test]
+    fn ping_pong_turns_at_both_ends() {
+        let mut playhead = playhead(PlayMode::PingPong);
+        let mut shown = Vec::new();
+        for _ in 0..6 {
+            let events = step(&mut playhead, 3, 1.0);
+            if playhead.current == 0 {
+                assert_eq!(events, vec![Looped, FrameChanged(0)]);
+            }
+            shown.push(playhead.current);
+        }
+        assert_eq!(shown, vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn once_finishes_on_the_last_frame() {
+        let mut playhead = playhead(PlayMode::Once);
+        assert_eq!(step(&mut playhead, 3, 1.0), vec![FrameChanged(1)]);
+        assert_eq!(step(&mut playhead, 3, 1.0), vec![FrameChanged(2)]);
+        assert_eq!(step(&mut playhead, 3, 1.0), vec![Finished]);
+        assert_eq!(playhead.current, 2);
+        assert!(playhead.finished && !playhead.playing);
+        // Nothing more happens once finished
+        assert_eq!(step(&mut playhead, 3, 5.0), vec![]);
+    }
+
+    #[test]
+    fn large_dt_steps_over_several_frames() {
+        let mut playhead = playhead(PlayMode::Loop);
+        assert_eq!(step(&mut playhead, 3, 4.5), vec![FrameChanged(1), FrameChanged(2), Looped, FrameChanged(0), FrameChanged(1)]);
+        assert_eq!(playhead.current, 1);
+        assert!((playhead.elapsed - 0.5).abs() < 1e-6);
+
+        let mut playhead = self::playhead(PlayMode::Once);
+        assert_eq!(step(&mut playhead, 3, 10.0), vec![FrameChanged(1), FrameChanged(2), Finished]);
+        assert_eq!(playhead.elapsed, 0.0);
+    }
+
+    #[test]
+    fn frame_durations_and_speed_are_used() {
+        let mut playhead = playhead(PlayMode::Loop);
+        let durations = [0.5, 2.0];
+        assert_eq!(playhead.advance(2, |index| durations[index], 1.0), vec![FrameChanged(1)]);
+        assert_eq!(playhead.advance(2, |index| durations[index], 1.0), vec![]);
+        playhead.speed = 2.0;
+        assert_eq!(playhead.advance(2, |index| durations[index], 0.25), vec![Looped, FrameChanged(0)]);
+    }
+
+    #[test]
+    fn paused_or_empty_does_nothing() {
+        let mut playhead = playhead(PlayMode::Loop);
+        assert_eq!(step(&mut playhead, 0, 1.0), vec![]);
+        playhead.playing = false;
+        assert_eq!(step(&mut playhead, 3, 1.0), vec![]);
+        assert_eq!(playhead.current, 0);
+    }
+
+    #[test]
+    fn seek_resets_the_ping_pong_direction() {
+        let mut playhead = playhead(PlayMode::PingPong);
+        step(&mut playhead, 3, 3.0); // 1, 2, then back to 1 heading backwards
+        assert_eq!(playhead.current, 1);
+        assert!(!playhead.forward);
+        playhead.seek(1);
+        assert!(playhead.forward);
+        assert_eq!(step(&mut playhead, 3, 1.0), vec![FrameChanged(2)]);
+    }
+
+    #[test]
+    fn seek_clears_finished() {
+        let mut playhead = playhead(PlayMode::Once);
+        step(&mut playhead, 2, 5.0);
+        assert!(playhead.finished);
+        playhead.seek(0);
+        playhead.playing = true;
+        assert!(!playhead.finished);
+        assert_eq!(step(&mut playhead, 2, 1.0), vec![FrameChanged(1)]);
+    }
+}
@@ -0,0 +1,394 @@
+/*
+Made by: image_test contributors
+Date: 2026-10-18
+Program Details: CPU image processing (grayscale, blur, hue shift, ...) for loaded images
+
+To import you need:
+In the mod modules section add:
+    pub mod image_filters;
+
+Then add the following with the use commands:
+use crate::modules::image_filters::Filter;
+
+Every filter takes RGBA pixels and makes a new copy, the original is never changed.
+The results are the same on every machine, so they are safe to compare pixel by pixel.
+The filters themselves run on the CPU and need no window. apply_filters and insert_filtered
+read the pixels back from the GPU first, so they only work while the window is open.
+
+Changing a StillImage in place (the texture and transparency mask are rebuilt):
+    img.apply_filters(&[Filter::Grayscale, Filter::Brightness(-0.2)]);
+
+Adding an edited copy of a preloaded texture to the TextureManager:
+    texture_manager.insert_filtered("assets/hero.png", "hero_dark", &[Filter::Brightness(-0.4)]);
+    img.set_preload(texture_manager.get_preload("hero_dark").unwrap());
+
+Working on an Image directly:
+    let blurred = image_filters::apply(&image, Filter::GaussianBlur(2.0));
+    let inverted = image_filters::invert(&image);
+
+The filters:
+- Grayscale                 Perceived brightness of each pixel, as gray
+- Brightness(amount)        -1.0 is black, 0.0 unchanged, 1.0 is white
+- Contrast(amount)          0.0 is flat gray, 1.0 unchanged, above 1.0 more contrast
+- Invert                    Negative colours
+- HueShift(degrees)         Turn every colour around the colour wheel
+- BoxBlur(radius)           Average of a (2 * radius + 1) pixel square
+- GaussianBlur(sigma)       Smooth blur, sigma is roughly the blur size in pixels
+- Sharpen(amount)           Unsharp mask, 1.0 is a normal amount
+- Threshold(level)          Pure black or white, split at the given brightness (0 to 255)
+Alpha is kept as it is, except by the blurs which also soften the edges.
+*/
+use macroquad::prelude::*;
+use macroquad::color::{hsl_to_rgb, rgb_to_hsl};
+
+// One image processing step
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Grayscale,
+    Brightness(f32),
+    Contrast(f32),
+    Invert,
+    HueShift(f32),
+    BoxBlur(u32),
+    GaussianBlur(f32),
+    Sharpen(f32),
+    Threshold(u8),
+}
+
+// Run one filter, returning the processed copy
+pub fn apply(image: &Image, filter: Filter) -> Image {
+    match filter {
+        Filter::Grayscale => grayscale(image),
+        Filter::Brightness(amount) => brightness(image, amount),
+        Filter::Contrast(amount) => contrast(image, amount),
+        Filter::Invert => invert(image),
+        Filter::HueShift(degrees) => hue_shift(image, degrees),
+        Filter::BoxBlur(radius) => box_blur(image, radius),
+        Filter::GaussianBlur(sigma) => gaussian_blur(image, sigma),
+        Filter::Sharpen(amount) => sharpen(image, amount),
+        Filter::Threshold(level) => threshold(image, level),
+    }
+}
+
+// Run several filters in order
+pub fn apply_all(image: &Image, filters: &[Filter]) -> Image {
+    let mut result = image.clone();
+    for &filter in filters {
+        result = apply(&result, filter);
+    }
+    result
+}
+
+// Copy a texture's pixels back from the GPU, cropped to region when given (atlas entries)
+// Needs a live GL context, so call it from inside the macroquad main loop, not from tests
+pub fn read_pixels(texture: &Texture2D, region: Option<Rect>) -> Image {
+    let image = texture.get_texture_data();
+    match region {
+        Some(region) => image.sub_image(region),
+        None => image,
+    }
+}
+
+// Perceived brightness of an RGB pixel (ITU-R BT.601 weights), in integer math so it is exact
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114 + 500) / 1000) as u8
+}
+
+// Copy an image, changing the RGB of each pixel and keeping its alpha
+fn map_rgb(image: &Image, f: impl Fn(u8, u8, u8) -> (u8, u8, u8)) -> Image {
+    let mut result = image.clone();
+    for pixel in result.bytes.chunks_exact_mut(4) {
+        let (r, g, b) = f(pixel[0], pixel[1], pixel[2]);
+        pixel[0] = r;
+        pixel[1] = g;
+        pixel[2] = b;
+    }
+    result
+}
+
+// Round a colour channel value back into 0..=255
+fn to_channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+#[allow(unused)]
+pub fn grayscale(image: &Image) -> Image {
+    map_rgb(image, |r, g, b| {
+        let gray = luma(r, g, b);
+        (gray, gray, gray)
+    })
+}
+
+// Shift every channel towards black (negative amounts) or white (positive), -1.0 to 1.0
+#[allow(unused)]
+pub fn brightness(image: &Image, amount: f32) -> Image {
+    let offset = amount.clamp(-1.0, 1.0) * 255.0;
+    let adjust = |channel: u8| to_channel(channel as f32 + offset);
+    map_rgb(image, |r, g, b| (adjust(r), adjust(g), adjust(b)))
+}
+
+// Push every channel away from (above 1.0) or towards (below 1.0) middle gray
+#[allow(unused)]
+pub fn contrast(image: &Image, amount: f32) -> Image {
+    let amount = amount.max(0.0);
+    let adjust = |channel: u8| to_channel((channel as f32 - 127.5) * amount + 127.5);
+    map_rgb(image, |r, g, b| (adjust(r), adjust(g), adjust(b)))
+}
+
+#[allow(unused)]
+pub fn invert(image: &Image) -> Image {
+    map_rgb(image, |r, g, b| (255 - r, 255 - g, 255 - b))
+}
+
+// Rotate every colour's hue, keeping its saturation and lightness. 360 degrees is a full turn
+#[allow(unused)]
+pub fn hue_shift(image: &Image, degrees: f32) -> Image {
+    let turn = (degrees / 360.0).rem_euclid(1.0);
+    map_rgb(image, |r, g, b| {
+        let (h, s, l) = rgb_to_hsl(Color::from_rgba(r, g, b, 255));
+        let shifted = hsl_to_rgb((h + turn).rem_euclid(1.0), s, l);
+        (
+            to_channel(shifted.r * 255.0),
+            to_channel(shifted.g * 255.0),
+            to_channel(shifted.b * 255.0),
+        )
+    })
+}
+
+// Black where a pixel's brightness is at or below level, white above it
+#[allow(unused)]
+pub fn threshold(image: &Image, level: u8) -> Image {
+    map_rgb(image, |r, g, b| {
+        if luma(r, g, b) > level {
+            (255, 255, 255)
+        } else {
+            (0, 0, 0)
+        }
+    })
+}
+
+// Average of the (2 * radius + 1) square around each pixel. Edges repeat the border pixels
+#[allow(unused)]
+pub fn box_blur(image: &Image, radius: u32) -> Image {
+    let size = radius as usize * 2 + 1;
+    convolve(image, &vec![1.0 / size as f32; size])
+}
+
+// Gaussian blur with the given standard deviation in pixels
+#[allow(unused)]
+pub fn gaussian_blur(image: &Image, sigma: f32) -> Image {
+    convolve(image, &gaussian_kernel(sigma))
+}
+
+// Unsharp mask: add back the difference between the image and a slightly blurred copy
+#[allow(unused)]
+pub fn sharpen(image: &Image, amount: f32) -> Image {
+    let blurred = gaussian_blur(image, 1.0);
+    let mut result = image.clone();
+    for (pixel, soft) in result.bytes.chunks_exact_mut(4).zip(blurred.bytes.chunks_exact(4)) {
+        for channel in 0..3 {
+            let original = pixel[channel] as f32;
+            pixel[channel] = to_channel(original + (original - soft[channel] as f32) * amount);
+        }
+    }
+    result
+}
+
+// Normalized 1D Gaussian weights reaching out three standard deviations
+fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    if sigma <= 0.0 {
+        return vec![1.0];
+    }
+    let radius = (sigma * 3.0).ceil() as i32;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|offset| (-((offset * offset) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    weights.iter().map(|weight| weight / total).collect()
+}
+
+// Blur with a symmetric 1D kernel, once across and once down
+// Colours are weighted by alpha while blurring so transparent pixels don't darken the edges
+fn convolve(image: &Image, kernel: &[f32]) -> Image {
+    let width = image.width as usize;
+    let height = image.height as usize;
+    if width == 0 || height == 0 || kernel.len() <= 1 {
+        return image.clone();
+    }
+    // Premultiplied alpha, as floats
+    let mut pixels: Vec<[f32; 4]> = image
+        .bytes
+        .chunks_exact(4)
+        .map(|p| {
+            let alpha = p[3] as f32 / 255.0;
+            [p[0] as f32 * alpha, p[1] as f32 * alpha, p[2] as f32 * alpha, p[3] as f32]
+        })
+        .collect();
+    pixels = blur_pass(&pixels, width, height, kernel, true);
+    pixels = blur_pass(&pixels, width, height, kernel, false);
+
+    let mut result = image.clone();
+    for (out, pixel) in result.bytes.chunks_exact_mut(4).zip(pixels) {
+        let alpha = pixel[3] / 255.0;
+        for channel in 0..3 {
+            out[channel] = if alpha > 0.0 { to_channel(pixel[channel] / alpha) } else { 0 };
+        }
+        out[3] = to_channel(pixel[3]);
+    }
+    result
+}
+
+// One direction of a separable blur, clamping samples to the image edge
+fn blur_pass(pixels: &[[f32; 4]], width: usize, height: usize, kernel: &[f32], horizontal: bool) -> Vec<[f32; 4]> {
+    let radius = (kernel.len() / 2) as isize;
+    let mut result = vec![[0.0; 4]; pixels.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0; 4];
+            for (i, weight) in kernel.iter().enumerate() {
+                let offset = i as isize - radius;
+                let (sx, sy) = if horizontal {
+                    ((x as isize + offset).clamp(0, width as isize - 1) as usize, y)
+                } else {
+                    (x, (y as isize + offset).clamp(0, height as isize - 1) as usize)
+                };
+                let sample = pixels[sy * width + sx];
+                for channel in 0..4 {
+                    sum[channel] += sample[channel] * weight;
+                }
+            }
+            result[y * width + x] = sum;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An image of the given width from a list of RGBA pixels, row by row
+    fn image(width: u16, pixels: &[[u8; 4]]) -> Image {
+        Image {
+            bytes: pixels.concat(),
+            width,
+            height: (pixels.len() / width as usize) as u16,
+        }
+    }
+
+    fn pixels(image: &Image) -> Vec<[u8; 4]> {
+        image.bytes.chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]).collect()
+    }
+
+    #[test]
+    fn grayscale_uses_luma_weights() {
+        let source = image(4, &[[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [10, 20, 30, 255]]);
+        assert_eq!(
+            pixels(&grayscale(&source)),
+            vec![[76, 76, 76, 255], [150, 150, 150, 255], [29, 29, 29, 255], [18, 18, 18, 255]]
+        );
+    }
+
+    #[test]
+    fn brightness_clamps() {
+        let source = image(2, &[[10, 20, 30, 255], [200, 250, 255, 255]]);
+        assert_eq!(pixels(&brightness(&source, 0.5)), vec![[138, 148, 158, 255], [255, 255, 255, 255]]);
+        assert_eq!(pixels(&brightness(&source, -0.5)), vec![[0, 0, 0, 255], [73, 123, 128, 255]]);
+        // Amounts past +-1.0 act like +-1.0
+        assert_eq!(pixels(&brightness(&source, 3.0)), vec![[255, 255, 255, 255]; 2]);
+        assert_eq!(pixels(&brightness(&source, -3.0)), vec![[0, 0, 0, 255]; 2]);
+    }
+
+    #[test]
+    fn contrast_pushes_from_middle_gray() {
+        let source = image(2, &[[10, 20, 30, 255], [128, 200, 255, 255]]);
+        assert_eq!(pixels(&contrast(&source, 2.0)), vec![[0, 0, 0, 255], [129, 255, 255, 255]]);
+        assert_eq!(pixels(&contrast(&source, 1.0)), pixels(&source));
+        assert_eq!(pixels(&contrast(&source, 0.0)), vec![[128, 128, 128, 255]; 2]);
+    }
+
+    #[test]
+    fn invert_flips_channels() {
+        let source = image(2, &[[255, 0, 0, 255], [10, 20, 30, 255]]);
+        assert_eq!(pixels(&invert(&source)), vec![[0, 255, 255, 255], [245, 235, 225, 255]]);
+        assert_eq!(pixels(&invert(&invert(&source))), pixels(&source));
+    }
+
+    #[test]
+    fn hue_shift_turns_red_to_green_and_blue() {
+        let red = image(1, &[[255, 0, 0, 255]]);
+        assert_eq!(pixels(&hue_shift(&red, 120.0)), vec![[0, 255, 0, 255]]);
+        assert_eq!(pixels(&hue_shift(&red, 240.0)), vec![[0, 0, 255, 255]]);
+        assert_eq!(pixels(&hue_shift(&red, -120.0)), vec![[0, 0, 255, 255]]);
+        assert_eq!(pixels(&hue_shift(&red, 360.0)), vec![[255, 0, 0, 255]]);
+    }
+
+    #[test]
+    fn threshold_splits_at_level() {
+        let source = image(3, &[[255, 0, 0, 255], [100, 100, 100, 255], [101, 101, 101, 255]]);
+        assert_eq!(
+            pixels(&threshold(&source, 100)),
+            vec![[0, 0, 0, 255], [0, 0, 0, 255], [255, 255, 255, 255]]
+        );
+    }
+
+    #[test]
+    fn box_blur_averages_neighbours() {
+        let black = [0, 0, 0, 255];
+        let white = [255, 255, 255, 255];
+        let gray = [85, 85, 85, 255];
+        let row = image(5, &[black, black, white, black, black]);
+        assert_eq!(pixels(&box_blur(&row, 1)), vec![black, gray, gray, gray, black]);
+        // A single white pixel in a 3x3 block is inside every pixel's 3x3 square, so it
+        // spreads evenly: 255 / 9 everywhere
+        let mut block = vec![black; 9];
+        block[4] = white;
+        assert_eq!(pixels(&box_blur(&image(3, &block), 1)), vec![[28, 28, 28, 255]; 9]);
+        // Radius 0 leaves the image as it is
+        assert_eq!(pixels(&box_blur(&row, 0)), pixels(&row));
+    }
+
+    #[test]
+    fn blurs_keep_uniform_images() {
+        let source = image(3, &[[40, 80, 120, 255]; 9]);
+        assert_eq!(pixels(&gaussian_blur(&source, 1.5)), pixels(&source));
+        assert_eq!(pixels(&sharpen(&source, 1.0)), pixels(&source));
+        assert_eq!(pixels(&box_blur(&source, 2)), pixels(&source));
+    }
+
+    #[test]
+    fn colour_filters_keep_alpha() {
+        let source = image(3, &[[255, 0, 0, 0], [10, 20, 30, 77], [200, 100, 50, 254]]);
+        let alphas = |image: &Image| pixels(image).iter().map(|p| p[3]).collect::<Vec<_>>();
+        let filters = [
+            Filter::Grayscale,
+            Filter::Brightness(0.3),
+            Filter::Contrast(1.7),
+            Filter::Invert,
+            Filter::HueShift(90.0),
+            Filter::Threshold(60),
+            Filter::Sharpen(1.0),
+        ];
+        for filter in filters {
+            assert_eq!(alphas(&apply(&source, filter)), vec![0, 77, 254], "{filter:?}");
+        }
+    }
+
+    #[test]
+    fn blur_ignores_transparent_colour() {
+        // A fully transparent neighbour doesn't darken the opaque pixel's colour
+        let source = image(2, &[[200, 100, 50, 255], [0, 0, 0, 0]]);
+        let blurred = pixels(&box_blur(&source, 1));
+        assert_eq!(&blurred[0][..3], &[200, 100, 50]);
+        assert_eq!(blurred[0][3], 170);
+        assert_eq!(blurred[1][3], 85);
+    }
+
+    #[test]
+    fn apply_all_runs_in_order() {
+        let source = image(1, &[[255, 0, 0, 255]]);
+        assert_eq!(pixels(&apply_all(&source, &[Filter::HueShift(120.0), Filter::Invert])), vec![[255, 0, 255, 255]]);
+        assert_eq!(pixels(&apply_all(&source, &[])), pixels(&source));
+    }
+}
@@ -6,9 +6,11 @@ Program Details: Central texture manager for preloading and sharing textures
 To use this:
 1. In the mod modules section add:
     pub mod image_preload;
+    pub mod still_image;
+    pub mod transparency_mask;
     
 2. Add the following use command:
-    use crate::modules::image_preload::TextureManager;
+    use crate::modules::image_preload::{TextureManager, TextureHandle};
 
 3. Create and initialize a TextureManager:
     let mut texture_manager = TextureManager::new();
@@ -20,6 +22,23 @@ To use this:
     // Or preload individual textures
     texture_manager.preload("assets/image3.png").await;
     
+    // Or choose how the transparency mask is built for one asset
+    // (see transparency_mask.rs for MaskSettings)
+    texture_manager.preload_with_mask("assets/shadow.png", MaskSettings::threshold(128)).await;
+    
+    // preload and preload_all also hand back TextureHandles, small copyable ids
+    // that are faster than path lookups and can be stored in your own structs
+    let handles = texture_manager.preload_all(&["assets/image1.png", "assets/image2.png"]).await;
+    let hero: TextureHandle = texture_manager.preload("assets/hero.png").await;
+    
+    // Or add an image you built in code; it is then looked up by the given name
+    texture_manager.insert_image("generated/red_square", Image::gen_image_color(32, 32, RED));
+    
+    // To keep running when an asset is missing or broken, use the try_ versions
+    if let Err(err) = texture_manager.try_preload("assets/maybe_missing.png").await {
+        println!("Skipping asset: {}", err);
+    }
+    
 5. Get preloaded textures for use with ImageObject - two approaches:
 
    // Approach 1: Using unwrap() - Simple but will panic if image doesn't exist
@@ -34,6 +53,10 @@ To use this:
        // Handle the error case (e.g., try to load it or use a placeholder)
    }
     
+   // Approach 3: Using a TextureHandle - no string lookup, and set_handle returns
+   // false instead of panicking if the texture has since been unloaded
+   image_obj.set_handle(&texture_manager, hero);
+    
 6. Access textures by index:
     // Using unwrap() approach:
     image_obj.set_preload(texture_manager.get_preload_by_index(0).unwrap());
@@ -60,57 +83,869 @@ To use this:
         }
     }
 
+9. Showing a placeholder instead of crashing on a bad asset path:
+    texture_manager.enable_fallback();
+    texture_manager.preload_all(&["assets/image1.png", "assets/typo.png"]).await;
+    
+    // "assets/typo.png" is now a magenta checkerboard whose filename is "__missing__:assets/typo.png"
+    for err in texture_manager.get_errors() {
+        println!("Failed to load: {}", err);
+    }
+
+10. Freeing textures you no longer need:
+    texture_manager.unload("assets/image1.png"); // Handles to it become stale
+    texture_manager.clear();                     // Unload everything
+    
+    // Re-read a file from disk after it changed, keeping the same handle
+    texture_manager.reload("assets/image2.png").await.ok();
+    
+    // Or cap memory use (in bytes) and let the least recently used textures be unloaded
+    texture_manager.set_memory_budget(Some(64 * 1024 * 1024));
+    println!("Using about {} bytes", texture_manager.memory_usage());
+
+11. Listing assets in a manifest file instead of in code:
+    // assets/manifest.json
+    {
+        "assets": [
+            { "path": "assets/hero.png", "alias": "hero", "tags": ["characters"], "filter": "nearest" },
+            { "path": "assets/tree.png", "alias": "tree", "tags": ["scenery"], "alpha_threshold": 128 },
+            { "path": "assets/glow.png", "mask_mode": "alpha" }
+        ]
+    }
+    Only "path" is required. "filter" is "linear" (default) or "nearest",
+    "mask_mode" is "bitmask" (default) or "alpha".
+    The filter stays with the entry through reload and hot reload (see also set_filter).
+    A path that was already loaded with other mask settings is read again with the manifest's.
+    
+    texture_manager.load_manifest("assets/manifest.json").await.unwrap();
+    image_obj.set_preload(texture_manager.get_preload("hero").unwrap());
+    for handle in texture_manager.get_group("scenery") {
+        // ...
+    }
+
+12. Preloading a whole folder (native builds only) and finding textures by pattern:
+    // Files are loaded in natural order, so img2.png comes before img10.png
+    texture_manager.preload_dir("assets/photos", "*.png|*.jpg").await.unwrap();
+    
+    // Handles for every loaded path matching a pattern, in load order
+    let photos = texture_manager.get_matching("*.jpg");
+    
+    // Just the file paths preload_dir would load, in the same order, without loading them
+    let paths = image_preload::list_dir("assets/photos", "*.png|*.jpg").unwrap();
+
+13. Loading a long list of files without freezing the window:
+    texture_manager.queue_all(&["assets/image1.png", "assets/image2.png"]);
+    while texture_manager.is_loading() {
+        texture_manager.load_next().await;  // One file per frame
+        let progress = texture_manager.progress();
+        println!("{}/{} next: {:?}", progress.loaded, progress.total, progress.current);
+        next_frame().await;
+    }
+    
+    // Or let a LoadingScreen draw a progress bar for you (see loading_screen.rs)
+    LoadingScreen::new("Loading...").run(&mut texture_manager).await;
+
+14. Packing small images into shared textures (atlas mode):
+    // Turn this on before preloading. Each page is one 2048 x 2048 texture
+    texture_manager.enable_atlas(2048);
+    texture_manager.preload_all(&["assets/coin.png", "assets/gem.png", "assets/heart.png"]).await;
+    println!("Packed into {} page(s)", texture_manager.atlas_page_count());
+    
+    // Nothing else changes: set_preload and set_handle hand StillImage the page texture
+    // plus a source rectangle, and draw() only draws that part
+    
+    // unload, reload and clear give page space back once no StillImage still shows
+    // the old image, and empty pages are freed
+
+15. Reloading art while the game runs (native builds only):
+    texture_manager.enable_hot_reload(0.5); // Check files every half second
+    
+    // In the loop:
+    for change in texture_manager.update_hot_reload().await {
+        println!("{} changed: {:?}", change.path, change.result);
+    }
+    img.refresh(&texture_manager); // Picks up the new texture if img was set with set_handle
+    
+    // Images given their texture with set_preload keep the old one; use set_handle for
+    // anything that should follow edits. Missing file placeholders load once the file appears
+
+16. Building images into the program (single-file native builds and wasm without an assets folder):
+    // Everything in the assets folder, listed by build.rs at compile time
+    texture_manager.preload_embedded(crate::embedded_assets!());
+    
+    // Or only the files you name
+    texture_manager.preload_embedded(crate::embed_assets!["assets/image1.png", "assets/image2.png"]);
+    
+    // Or one file under any name you like
+    texture_manager.preload_bytes("hero", include_bytes!("../../assets/hero.png"));
+    
+    // Entries keep their "assets/..." paths, so a manifest loaded afterwards finds them
+    // already loaded instead of reading the files again. Embed the manifest too:
+    texture_manager.load_manifest_str("manifest.json", include_str!("../../assets/manifest.json")).await.unwrap();
+
+17. Animated GIF and APNG files:
+    // Every frame is decoded with its delay and stored with its own transparency mask
+    texture_manager.preload_animation("assets/fire.gif").await;
+    let mut fire = AnimatedImage::from_frames(img, texture_manager.get_animation("assets/fire.gif").unwrap());
+    
+    // get_preload on the path gives the first frame; later frames are "assets/fire.gif#1", "#2", ...
+    // Only the path itself counts in texture_count, get_texture_paths and get_preload_by_index
+    // unload and reload on the path act on every frame
+
+18. Edited copies of textures (see image_filters.rs):
+    texture_manager.insert_filtered("assets/image1.png", "image1_gray", &[Filter::Grayscale]);
+
 Note: For clearing images, use the clear() method directly on the ImageObject:
     image_obj.clear();
 */
 use macroquad::texture::Texture2D;
-use std::collections::HashMap;
-use crate::modules::still_image::set_texture_main;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use macroquad::texture::{FilterMode, Image};
+use serde::Deserialize;
+use macroquad::math::Rect;
+use crate::modules::still_image::{convert_io_error, convert_load_error, decode_frames, decode_image, missing_placeholder, try_texture_from_image, ImageError};
+use crate::modules::image_filters::{self, read_pixels, Filter};
+use crate::modules::texture_atlas::{AtlasRegion, TextureAtlas, MAX_PAGE_SIZE};
+use crate::modules::transparency_mask::{MaskMode, MaskSettings, TransparencyMask};
+
+/// Everything StillImage::set_preload needs: the texture, its shared mask, the filename,
+/// and the part of the texture to draw (Some for atlas entries, None for the whole texture)
+pub type PreloadedTexture = (Texture2D, Rc<TransparencyMask>, Rc<str>, Option<Rect>);
+
+/// A small copyable id for a texture stored in a TextureManager
+/// Cheaper than looking up by path, and can be kept in your own structs
+/// A handle goes stale once its texture is unloaded, even if the slot is reused later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureHandle {
+    index: u32,      // Position in the slot list
+    generation: u32, // Bumped every time the slot is emptied, so old handles stop matching
+}
+
+/// One storage slot for a texture, its shared mask and the filename handed out with them
+struct TextureSlot {
+    generation: u32,
+    entry: Option<PreloadedTexture>,
+    mask_settings: MaskSettings, // Kept so reload builds the mask the same way
+    bytes: usize, // Approximate memory used: width * height * 4 plus the mask
+    last_used: Cell<u64>, // Value of the manager's clock when last handed out, for LRU eviction
+    revision: u32, // Bumped whenever the entry is replaced, so StillImage::refresh can spot reloads
+    atlas_region: Option<AtlasRegion>, // Space reserved on an atlas page, given back when the entry goes
+    filter: Option<FilterMode>, // Set with set_filter, applied again to every texture stored in the slot
+}
+
+/// Every png, jpg, jpeg, tga and gif file under the assets folder, built into the program
+/// Expands to a &'static [(&'static str, &'static [u8])] of ("assets/name.png", file bytes),
+/// listed by build.rs so new files are picked up on the next build
+#[macro_export]
+macro_rules! embedded_assets {
+    () => {
+        include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"))
+    };
+}
+
+/// Build a chosen list of files into the program, paths relative to the folder with Cargo.toml
+/// Expands to the same slice type as embedded_assets!()
+#[macro_export]
+macro_rules! embed_assets {
+    ($($path:literal),* $(,)?) => {
+        &[$(($path, include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)) as &[u8])),*]
+    };
+}
+
+/// Approximate memory used by an image and its mask
+/// Uses the mask size rather than the texture so atlas entries only count their own area
+fn entry_bytes(mask: &TransparencyMask) -> usize {
+    mask.width() * mask.height() * 4 + mask.as_bytes().len()
+}
+
+/// Reasons an asset manifest can fail to load
+#[allow(unused)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestError {
+    NotFound(String),                        // The manifest file could not be read
+    Parse { path: String, message: String }, // The manifest is not valid JSON or has unknown fields
+    Image(ImageError),                       // One of the listed images failed to load
+}
+
+impl std::fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestError::NotFound(path) => write!(f, "manifest not found: {path}"),
+            ManifestError::Parse { path, message } => write!(f, "could not parse manifest {path}: {message}"),
+            ManifestError::Image(err) => write!(f, "manifest asset failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+/// Layout of a manifest file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    assets: Vec<ManifestAsset>,
+}
+
+/// One image listed in a manifest. Only path is required
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestAsset {
+    path: String,
+    #[serde(default)]
+    alias: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    filter: Option<ManifestFilter>,
+    #[serde(default)]
+    alpha_threshold: Option<u8>,
+    #[serde(default)]
+    mask_mode: Option<ManifestMaskMode>,
+}
+
+impl ManifestAsset {
+    /// Mask settings the manifest asks for, or None if it sets neither alpha_threshold nor mask_mode
+    fn mask_settings(&self) -> Option<MaskSettings> {
+        if self.alpha_threshold.is_none() && self.mask_mode.is_none() {
+            return None;
+        }
+        let mut mask_settings = MaskSettings::default();
+        if let Some(threshold) = self.alpha_threshold {
+            mask_settings.alpha_threshold = threshold;
+        }
+        if let Some(mode) = self.mask_mode {
+            mask_settings.mode = match mode {
+                ManifestMaskMode::Bitmask => MaskMode::Bitmask,
+                ManifestMaskMode::Alpha => MaskMode::Alpha,
+            };
+        }
+        Some(mask_settings)
+    }
+
+    /// Texture filter the manifest asks for, if any
+    fn filter_mode(&self) -> Option<FilterMode> {
+        self.filter.map(|filter| match filter {
+            ManifestFilter::Linear => FilterMode::Linear,
+            ManifestFilter::Nearest => FilterMode::Nearest,
+        })
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum ManifestFilter {
+    Linear,
+    Nearest,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum ManifestMaskMode {
+    Bitmask,
+    Alpha,
+}
+
+/// Snapshot of how far a queued load has got, from TextureManager::progress
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LoadProgress {
+    pub loaded: usize,           // Files finished so far (including ones that failed)
+    pub total: usize,            // Files queued in this batch
+    pub current: Option<String>, // File that will be loaded next, None once finished
+    pub bytes: usize,            // File bytes read so far
+}
+
+impl LoadProgress {
+    /// Fraction finished from 0.0 to 1.0. An empty batch counts as finished
+    #[allow(unused)]
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.loaded as f32 / self.total as f32
+        }
+    }
+    
+    /// Check if every queued file has been handled
+    #[allow(unused)]
+    pub fn is_done(&self) -> bool {
+        self.loaded >= self.total
+    }
+}
+
+/// Watches the modification time of every loaded file (native builds only)
+#[cfg(not(target_arch = "wasm32"))]
+struct HotReload {
+    interval: f64, // Seconds between checks
+    last_check: f64, // get_time() of the last check
+    modified: HashMap<String, std::time::SystemTime>, // Last seen modification time per path
+}
+
+/// Reported by TextureManager::update_hot_reload for every file that changed on disk
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetChange {
+    pub path: String,
+    pub result: Result<TextureHandle, ImageError>, // Err if the new file could not be loaded; the old texture is kept
+}
 
 /// A central texture manager to preload and share textures
 /// This reduces memory usage and prevents flickering when switching images
 pub struct TextureManager {
-    textures: HashMap<String, (Texture2D, Vec<u8>)>,
+    slots: Vec<TextureSlot>,
+    handles: HashMap<String, TextureHandle>, // Path (or name) to handle lookup
     load_order: Vec<String>, // Store just the order textures were loaded in
+    fallback_enabled: bool, // Substitute a placeholder instead of panicking when preload fails
+    errors: Vec<ImageError>, // Failures that were replaced by the placeholder
+    free_slots: Vec<u32>, // Emptied slots ready to be reused
+    memory_budget: Option<usize>, // Evict least recently used textures above this many bytes
+    memory_used: usize,
+    clock: Cell<u64>, // Ticks on every lookup to order entries by last use
+    aliases: HashMap<String, String>, // Short name to path, usable anywhere a path is accepted
+    tags: HashMap<String, Vec<String>>, // Group or tag name to the paths listed under it
+    animations: HashMap<String, Vec<(TextureHandle, f32)>>, // Animated file path to its frames and delays
+    atlas: Option<TextureAtlas>, // When set, small images are packed into shared atlas pages
+    #[cfg(not(target_arch = "wasm32"))]
+    hot_reload: Option<HotReload>, // Set while hot reload is enabled
+    pending: VecDeque<(String, MaskSettings)>, // Files queued for frame-by-frame loading
+    progress: LoadProgress, // Progress of the current queued batch
 }
 
 impl TextureManager {
     /// Create a new texture manager
     pub fn new() -> Self {
         Self {
-            textures: HashMap::new(),
+            slots: Vec::new(),
+            handles: HashMap::new(),
             load_order: Vec::new(),
+            fallback_enabled: false,
+            errors: Vec::new(),
+            free_slots: Vec::new(),
+            memory_budget: None,
+            memory_used: 0,
+            clock: Cell::new(0),
+            aliases: HashMap::new(),
+            tags: HashMap::new(),
+            animations: HashMap::new(),
+            atlas: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            hot_reload: None,
+            pending: VecDeque::new(),
+            progress: LoadProgress::default(),
+        }
+    }
+    
+    /// Turn an alias into its path. Anything that is not an alias is returned unchanged
+    fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(String::as_str).unwrap_or(name)
+    }
+    
+    /// Advance the usage clock and return the new time
+    fn tick(&self) -> u64 {
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+        now
+    }
+    
+    /// Store an entry under a name, replacing any existing entry with that name
+    /// Replacing keeps the same handle so StillImages and saved handles stay valid
+    fn store(&mut self, name: &str, entry: PreloadedTexture, mask_settings: MaskSettings) -> TextureHandle {
+        self.store_packed(name, entry, mask_settings, None, true)
+    }
+    
+    /// Store an entry along with the atlas space it was packed into, if any
+    /// A replaced entry's old atlas space is given back unless the new entry reuses it,
+    /// and the new texture gets the filter chosen with set_filter
+    /// Unlisted entries (animation frames after the first) are left out of the load order,
+    /// so texture_count, get_preload_by_index and get_matching see one entry per file
+    fn store_packed(&mut self, name: &str, entry: PreloadedTexture, mask_settings: MaskSettings, atlas_region: Option<AtlasRegion>, listed: bool) -> TextureHandle {
+        let bytes = entry_bytes(&entry.1);
+        let now = self.tick();
+        let handle = if let Some(&handle) = self.handles.get(name) {
+            let slot = &mut self.slots[handle.index as usize];
+            self.memory_used -= slot.bytes;
+            if let Some(filter) = slot.filter {
+                entry.0.set_filter(filter);
+            }
+            let old_entry = slot.entry.replace(entry);
+            slot.mask_settings = mask_settings;
+            slot.bytes = bytes;
+            slot.last_used.set(now);
+            slot.revision = slot.revision.wrapping_add(1);
+            let old_region = std::mem::replace(&mut slot.atlas_region, atlas_region);
+            if old_region != atlas_region {
+                self.release_region(old_region, old_entry);
+            }
+            handle
+        } else {
+            let handle = match self.free_slots.pop() {
+                Some(index) => {
+                    let slot = &mut self.slots[index as usize];
+                    slot.entry = Some(entry);
+                    slot.mask_settings = mask_settings;
+                    slot.bytes = bytes;
+                    slot.last_used.set(now);
+                    slot.atlas_region = atlas_region;
+                    slot.filter = None;
+                    TextureHandle {
+                        index,
+                        generation: slot.generation,
+                    }
+                }
+                None => {
+                    self.slots.push(TextureSlot {
+                        generation: 0,
+                        entry: Some(entry),
+                        mask_settings,
+                        bytes,
+                        last_used: Cell::new(now),
+                        revision: 0,
+                        atlas_region,
+                        filter: None,
+                    });
+                    TextureHandle {
+                        index: self.slots.len() as u32 - 1,
+                        generation: 0,
+                    }
+                }
+            };
+            self.handles.insert(name.to_string(), handle);
+            if listed {
+                self.load_order.push(name.to_string()); // Store just the load order
+            }
+            handle
+        };
+        self.memory_used += bytes;
+        self.enforce_budget(&[handle]);
+        handle
+    }
+    
+    /// Turn a decoded image into a stored entry, packing it into the atlas when atlas mode is on
+    /// Images that don't fit on an empty atlas page get their own texture
+    /// Replacing a packed entry with an image that fits in its old space redraws it in place
+    fn store_image(&mut self, name: &str, image: &Image, mask_settings: MaskSettings) -> Result<TextureHandle, ImageError> {
+        self.store_image_listed(name, image, mask_settings, true)
+    }
+    
+    /// store_image, choosing whether the entry goes in the load order
+    fn store_image_listed(&mut self, name: &str, image: &Image, mask_settings: MaskSettings, listed: bool) -> Result<TextureHandle, ImageError> {
+        // The old space is only drawn over when nothing but this slot still shows the old image
+        let old_region = self.handles.get(name).and_then(|handle| {
+            let slot = &self.slots[handle.index as usize];
+            let unshared = slot.entry.as_ref().is_some_and(|entry| Rc::strong_count(&entry.1) == 1);
+            slot.atlas_region.filter(|_| unshared)
+        });
+        let packed = match self.atlas.as_mut() {
+            Some(atlas) if image.width > 0 && image.height > 0 => old_region
+                .and_then(|region| atlas.replace(region, image).map(|(texture, source)| (texture, source, region)))
+                .or_else(|| atlas.add(image)),
+            _ => None,
+        };
+        let (entry, region) = match packed {
+            Some((texture, source, region)) => {
+                let mask = TransparencyMask::from_rgba_with(&image.bytes, image.width as usize, image.height as usize, mask_settings);
+                ((texture, Rc::new(mask), name.into(), Some(source)), Some(region))
+            }
+            None => {
+                let (texture, mask) = try_texture_from_image(name, image, mask_settings)?;
+                ((texture, Rc::new(mask), name.into(), None), None)
+            }
+        };
+        Ok(self.store_packed(name, entry, mask_settings, region, listed))
+    }
+    
+    /// Give atlas space back to the atlas so later images can use it
+    /// The space is only reused after every StillImage showing the old entry has let go of it
+    fn release_region(&mut self, region: Option<AtlasRegion>, old_entry: Option<PreloadedTexture>) {
+        match (self.atlas.as_mut(), region, old_entry) {
+            (Some(atlas), Some(region), Some((_, mask, _, _))) => atlas.remove_when_unused(region, &mask),
+            (Some(atlas), Some(region), None) => atlas.remove(region),
+            _ => {}
+        }
+    }
+    
+    /// Swap in a new atlas (or none). Entries packed into the old one keep drawing from its pages,
+    /// which are freed once those entries are unloaded, but their space is no longer tracked
+    fn replace_atlas(&mut self, atlas: Option<TextureAtlas>) {
+        for slot in &mut self.slots {
+            slot.atlas_region = None;
+        }
+        self.atlas = atlas;
+    }
+    
+    /// Read, decode and store a file. Returns the handle and how many file bytes were read
+    async fn load_and_store(&mut self, path: &str, mask_settings: MaskSettings) -> Result<(TextureHandle, usize), ImageError> {
+        let bytes = macroquad::file::load_file(path).await.map_err(|err| convert_load_error(path, err))?;
+        let image = decode_image(path, &bytes)?;
+        Ok((self.store_image(path, &image, mask_settings)?, bytes.len()))
+    }
+    
+    /// Read a GIF or APNG and store every frame. Frame 0 is stored under the path itself,
+    /// later frames under "path#1", "path#2" and so on, which are kept out of the load order
+    async fn load_and_store_animation(&mut self, path: &str, mask_settings: MaskSettings) -> Result<Vec<(TextureHandle, f32)>, ImageError> {
+        let bytes = macroquad::file::load_file(path).await.map_err(|err| convert_load_error(path, err))?;
+        let decoded = decode_frames(path, &bytes)?;
+        // Hold off eviction until every frame is stored, so later frames can't push out earlier ones
+        let budget = self.memory_budget.take();
+        let stored: Result<Vec<(TextureHandle, f32)>, ImageError> = decoded
+            .into_iter()
+            .enumerate()
+            .map(|(index, (image, delay))| {
+                let name = if index == 0 { path.to_string() } else { format!("{path}#{index}") };
+                Ok((self.store_image_listed(&name, &image, mask_settings, index == 0)?, delay))
+            })
+            .collect();
+        self.memory_budget = budget;
+        let frames = stored?;
+        // A reload with fewer frames than before drops the leftover ones
+        if let Some(old_frames) = self.animations.insert(path.to_string(), frames.clone()) {
+            for (handle, _) in old_frames.into_iter().skip(frames.len()) {
+                if self.is_valid(handle) {
+                    self.remove_handle(handle);
+                }
+            }
+        }
+        let keep: Vec<TextureHandle> = frames.iter().map(|(handle, _)| *handle).collect();
+        self.enforce_budget(&keep);
+        Ok(frames)
+    }
+    
+    /// Unload least recently used textures until memory use fits the budget
+    /// The textures in keep (the ones just stored) are never evicted, even if they alone are over budget
+    fn enforce_budget(&mut self, keep: &[TextureHandle]) {
+        let Some(budget) = self.memory_budget else {
+            return;
+        };
+        while self.memory_used > budget {
+            // Every named entry, including animation frames that are not in the load order
+            let oldest = self
+                .handles
+                .values()
+                .copied()
+                .filter(|handle| !keep.contains(handle))
+                .min_by_key(|handle| self.slots[handle.index as usize].last_used.get());
+            match oldest {
+                Some(handle) => self.remove_handle(handle),
+                None => break,
+            }
+        }
+    }
+    
+    /// Empty a slot and forget its name. The generation bump makes old handles stale
+    fn remove_handle(&mut self, handle: TextureHandle) {
+        let slot = &mut self.slots[handle.index as usize];
+        let old_entry = slot.entry.take();
+        slot.generation = slot.generation.wrapping_add(1);
+        self.memory_used -= slot.bytes;
+        slot.bytes = 0;
+        let region = slot.atlas_region.take();
+        self.free_slots.push(handle.index);
+        self.release_region(region, old_entry);
+        self.handles.retain(|_, stored| *stored != handle);
+        self.load_order.retain(|name| self.handles.contains_key(name));
+    }
+    
+    /// Preload a texture by its file path and get a handle to it
+    /// Panics if the file cannot be loaded, use try_preload to handle the error instead
+    pub async fn preload(&mut self, path: &str) -> TextureHandle {
+        self.preload_with_mask(path, MaskSettings::default()).await
+    }
+    
+    /// Preload a texture, choosing the alpha threshold and mode for its transparency mask
+    /// With fallback enabled a failed load is stored as the missing texture placeholder instead of panicking
+    pub async fn preload_with_mask(&mut self, path: &str, mask_settings: MaskSettings) -> TextureHandle {
+        match self.try_preload_with_mask(path, mask_settings).await {
+            Ok(handle) => handle,
+            Err(err) => {
+                if !self.fallback_enabled {
+                    panic!("{err}");
+                }
+                self.errors.push(err);
+                self.store(path, missing_placeholder(path), mask_settings)
+            }
+        }
+    }
+    
+    /// Preload a texture, returning an error instead of panicking if it cannot be loaded
+    #[allow(unused)]
+    pub async fn try_preload(&mut self, path: &str) -> Result<TextureHandle, ImageError> {
+        self.try_preload_with_mask(path, MaskSettings::default()).await
+    }
+    
+    /// Fallible preload that also chooses how the transparency mask is built
+    /// A path that is already loaded just returns its existing handle
+    pub async fn try_preload_with_mask(&mut self, path: &str, mask_settings: MaskSettings) -> Result<TextureHandle, ImageError> {
+        if let Some(&handle) = self.handles.get(path) {
+            return Ok(handle);
+        }
+        let (handle, _) = self.load_and_store(path, mask_settings).await?;
+        Ok(handle)
+    }
+    
+    /// Preload a texture from encoded file bytes already in memory (for example from include_bytes!)
+    /// The name is used for lookups just like a path. Panics if the bytes cannot be decoded,
+    /// unless fallback is enabled
+    #[allow(unused)]
+    pub fn preload_bytes(&mut self, name: &str, bytes: &[u8]) -> TextureHandle {
+        self.preload_bytes_with_mask(name, bytes, MaskSettings::default())
+    }
+    
+    /// Preload a texture from in-memory file bytes, choosing how its transparency mask is built
+    pub fn preload_bytes_with_mask(&mut self, name: &str, bytes: &[u8], mask_settings: MaskSettings) -> TextureHandle {
+        match self.try_preload_bytes_with_mask(name, bytes, mask_settings) {
+            Ok(handle) => handle,
+            Err(err) => {
+                if !self.fallback_enabled {
+                    panic!("{err}");
+                }
+                self.errors.push(err);
+                self.store(name, missing_placeholder(name), mask_settings)
+            }
+        }
+    }
+    
+    /// Preload a texture from in-memory file bytes, returning an error instead of panicking
+    #[allow(unused)]
+    pub fn try_preload_bytes(&mut self, name: &str, bytes: &[u8]) -> Result<TextureHandle, ImageError> {
+        self.try_preload_bytes_with_mask(name, bytes, MaskSettings::default())
+    }
+    
+    /// Fallible preload_bytes that also chooses how the transparency mask is built
+    /// A name that is already loaded just returns its existing handle
+    pub fn try_preload_bytes_with_mask(&mut self, name: &str, bytes: &[u8], mask_settings: MaskSettings) -> Result<TextureHandle, ImageError> {
+        if let Some(&handle) = self.handles.get(name) {
+            return Ok(handle);
         }
+        let image = decode_image(name, bytes)?;
+        self.store_image(name, &image, mask_settings)
+    }
+    
+    /// Preload every file from embedded_assets!() or embed_assets![...], returning handles in the same order
+    /// Each texture is stored under its path, so get_preload("assets/image1.png") and manifests still work
+    #[allow(unused)]
+    pub fn preload_embedded(&mut self, assets: &[(&str, &[u8])]) -> Vec<TextureHandle> {
+        assets
+            .iter()
+            .map(|(name, bytes)| self.preload_bytes(name, bytes))
+            .collect()
+    }
+    
+    /// Preload every frame of an animated GIF or APNG, returning the frame handles in order
+    /// Each frame gets its own transparency mask. Other images load as a single frame
+    /// With fallback enabled a failed load becomes a one-frame placeholder instead of panicking
+    #[allow(unused)]
+    pub async fn preload_animation(&mut self, path: &str) -> Vec<TextureHandle> {
+        self.preload_animation_with_mask(path, MaskSettings::default()).await
     }
     
-    /// Preload a texture by its file path
-    pub async fn preload(&mut self, path: &str) {
-        if !self.textures.contains_key(path) {
-            let (texture, mask) = set_texture_main(path).await;
-            self.textures.insert(path.to_string(), (texture, mask));
-            self.load_order.push(path.to_string()); // Store just the load order
+    /// Preload an animation, choosing how the transparency mask of every frame is built
+    pub async fn preload_animation_with_mask(&mut self, path: &str, mask_settings: MaskSettings) -> Vec<TextureHandle> {
+        match self.try_preload_animation_with_mask(path, mask_settings).await {
+            Ok(handles) => handles,
+            Err(err) => {
+                if !self.fallback_enabled {
+                    panic!("{err}");
+                }
+                self.errors.push(err);
+                vec![self.store(path, missing_placeholder(path), mask_settings)]
+            }
         }
     }
     
-    /// Preload multiple textures at once
+    /// Preload an animation, returning an error instead of panicking if it cannot be loaded
     #[allow(unused)]
-    pub async fn preload_all(&mut self, paths: &[&str]) {
+    pub async fn try_preload_animation(&mut self, path: &str) -> Result<Vec<TextureHandle>, ImageError> {
+        self.try_preload_animation_with_mask(path, MaskSettings::default()).await
+    }
+    
+    /// Fallible preload_animation that also chooses how the transparency masks are built
+    /// An animation that is already loaded just returns its existing frame handles,
+    /// unless the memory budget unloaded some of its frames, in which case it is read again
+    pub async fn try_preload_animation_with_mask(&mut self, path: &str, mask_settings: MaskSettings) -> Result<Vec<TextureHandle>, ImageError> {
+        if let Some(frames) = self.animations.get(path) {
+            if frames.iter().all(|(handle, _)| self.is_valid(*handle)) {
+                return Ok(frames.iter().map(|(handle, _)| *handle).collect());
+            }
+        }
+        let frames = self.load_and_store_animation(path, mask_settings).await?;
+        Ok(frames.into_iter().map(|(handle, _)| handle).collect())
+    }
+    
+    /// Get every frame of a loaded animation with its delay in seconds,
+    /// ready for AnimatedImage::from_frames. Frames unloaded by the memory budget are skipped
+    #[allow(unused)]
+    pub fn get_animation(&self, path: &str) -> Option<Vec<(PreloadedTexture, f32)>> {
+        let frames = self.animations.get(self.resolve(path))?;
+        Some(
+            frames
+                .iter()
+                .filter_map(|&(handle, delay)| self.get(handle).map(|preloaded| (preloaded, delay)))
+                .collect(),
+        )
+    }
+    
+    /// Add an image that is already in memory (for example one built procedurally)
+    /// Uses the same texture and mask pipeline as preload. An existing entry with the same name is replaced
+    #[allow(unused)]
+    pub fn insert_image(&mut self, name: &str, image: Image) -> TextureHandle {
+        self.insert_image_with_mask(name, image, MaskSettings::default())
+    }
+    
+    /// Add an in-memory image, choosing the alpha threshold and mode for its transparency mask
+    #[allow(unused)]
+    pub fn insert_image_with_mask(&mut self, name: &str, image: Image, mask_settings: MaskSettings) -> TextureHandle {
+        self.store_image(name, &image, mask_settings)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+    
+    /// Store a processed copy of a loaded texture under a new name (see image_filters.rs)
+    /// The copy's mask is built with the same settings as the original's
+    /// Returns None if the source is not loaded
+    /// The source pixels are read back from the GPU, so this needs the window to be open
+    #[allow(unused)]
+    pub fn insert_filtered(&mut self, source: &str, name: &str, filters: &[Filter]) -> Option<TextureHandle> {
+        let handle = self.get_handle(source)?;
+        let (texture, _, _, region) = self.get(handle)?;
+        let mask_settings = self.slots[handle.index as usize].mask_settings;
+        let image = image_filters::apply_all(&read_pixels(&texture, region), filters);
+        self.store_image(name, &image, mask_settings).ok()
+    }
+    
+    /// Preload multiple textures at once, returning their handles in the same order
+    #[allow(unused)]
+    pub async fn preload_all(&mut self, paths: &[&str]) -> Vec<TextureHandle> {
+        let mut handles = Vec::with_capacity(paths.len());
         for path in paths {
-            self.preload(path).await;
+            handles.push(self.preload(path).await);
         }
+        handles
     }
     
-    /// Get a preloaded texture for use in an ImageObject
+    /// Preload multiple textures, stopping at the first one that fails
+    /// Textures loaded before the failure stay available
+    #[allow(unused)]
+    pub async fn try_preload_all(&mut self, paths: &[&str]) -> Result<Vec<TextureHandle>, ImageError> {
+        let mut handles = Vec::with_capacity(paths.len());
+        for path in paths {
+            handles.push(self.try_preload(path).await?);
+        }
+        Ok(handles)
+    }
+    
+    /// Add a file to the loading queue. Nothing is loaded until load_next is called
+    /// Queuing after the previous batch finished starts a fresh count
+    #[allow(unused)]
+    pub fn queue(&mut self, path: &str) {
+        self.queue_with_mask(path, MaskSettings::default());
+    }
+    
+    /// Add a file to the loading queue, choosing how its transparency mask is built
+    #[allow(unused)]
+    pub fn queue_with_mask(&mut self, path: &str, mask_settings: MaskSettings) {
+        if self.pending.is_empty() {
+            self.progress = LoadProgress::default();
+        }
+        self.pending.push_back((path.to_string(), mask_settings));
+        self.progress.total += 1;
+        self.progress.current = self.pending.front().map(|(next, _)| next.clone());
+    }
+    
+    /// Add several files to the loading queue
+    #[allow(unused)]
+    pub fn queue_all(&mut self, paths: &[&str]) {
+        for path in paths {
+            self.queue(path);
+        }
+    }
+    
+    /// Load the next queued file. Call once per frame so the window keeps drawing while assets load
+    /// Returns None when the queue is empty. A failed file is still counted as loaded so the
+    /// batch always finishes; with fallback enabled it becomes the placeholder instead of an Err
     #[allow(unused)]
-    pub fn get_preload(&self, path: &str) -> Option<(Texture2D, Vec<u8>, String)> {
-        self.textures.get(path).map(|(texture, mask)| 
-            (texture.clone(), mask.clone(), path.to_string())
+    pub async fn load_next(&mut self) -> Option<Result<TextureHandle, ImageError>> {
+        let (path, mask_settings) = self.pending.pop_front()?;
+        let result = match self.handles.get(&path) {
+            Some(&handle) => Ok(handle),
+            None => self.load_and_store(&path, mask_settings).await.map(|(handle, bytes)| {
+                self.progress.bytes += bytes;
+                handle
+            }),
+        };
+        let result = match result {
+            Err(err) if self.fallback_enabled => {
+                self.errors.push(err);
+                Ok(self.store(&path, missing_placeholder(&path), mask_settings))
+            }
+            other => other,
+        };
+        self.progress.loaded += 1;
+        self.progress.current = self.pending.front().map(|(next, _)| next.clone());
+        Some(result)
+    }
+    
+    /// Progress of the current queued batch
+    #[allow(unused)]
+    pub fn progress(&self) -> &LoadProgress {
+        &self.progress
+    }
+    
+    /// Check if there are still queued files waiting for load_next
+    #[allow(unused)]
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty()
+    }
+    
+    /// Get a preloaded texture by its handle
+    /// Returns None if the handle is stale (its texture was unloaded)
+    #[allow(unused)]
+    pub fn get(&self, handle: TextureHandle) -> Option<PreloadedTexture> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.last_used.set(self.tick());
+        slot.entry.as_ref().map(|(texture, mask, filename, source)| 
+            (texture.clone(), Rc::clone(mask), Rc::clone(filename), *source)
         )
     }
     
+    /// How many times the texture behind a handle has been replaced (by reload or hot reload)
+    /// Returns None if the handle is stale
+    #[allow(unused)]
+    pub fn revision(&self, handle: TextureHandle) -> Option<u32> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation || slot.entry.is_none() {
+            return None;
+        }
+        Some(slot.revision)
+    }
+    
+    /// Check if a handle still points at a loaded texture
+    #[allow(unused)]
+    pub fn is_valid(&self, handle: TextureHandle) -> bool {
+        self.slots
+            .get(handle.index as usize)
+            .is_some_and(|slot| slot.generation == handle.generation && slot.entry.is_some())
+    }
+    
+    /// Look up the handle for a path (or name) that has been loaded
+    #[allow(unused)]
+    pub fn get_handle(&self, path: &str) -> Option<TextureHandle> {
+        self.handles.get(self.resolve(path)).copied()
+    }
+    
+    /// Get a preloaded texture for use in an ImageObject
+    /// Only reference counts are bumped, the mask and filename are shared rather than copied
+    #[allow(unused)]
+    pub fn get_preload(&self, path: &str) -> Option<PreloadedTexture> {
+        self.get(self.get_handle(path)?)
+    }
+    
     /// Get a preloaded texture by its index in the preload order
     #[allow(unused)]
-    pub fn get_preload_by_index(&self, index: usize) -> Option<(Texture2D, Vec<u8>, String)> {
+    pub fn get_preload_by_index(&self, index: usize) -> Option<PreloadedTexture> {
         if index < self.load_order.len() {
             let path = &self.load_order[index];
             self.get_preload(path)
@@ -119,6 +954,365 @@ impl TextureManager {
         }
     }
     
+    /// Unload a texture by path. Returns false if it was not loaded
+    /// Handles to it become stale and index-based access shifts down to fill the gap
+    /// StillImages already showing it keep drawing it until they change texture
+    /// (in atlas mode its page space is only reused after that)
+    #[allow(unused)]
+    pub fn unload(&mut self, path: &str) -> bool {
+        // Unloading an animation by its path unloads every frame
+        let resolved = self.resolve(path).to_string();
+        if let Some(frames) = self.animations.remove(&resolved) {
+            for (handle, _) in frames {
+                if self.is_valid(handle) {
+                    self.remove_handle(handle);
+                }
+            }
+            return true;
+        }
+        match self.get_handle(path) {
+            Some(handle) => {
+                self.remove_handle(handle);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Unload every texture. All existing handles become stale
+    /// Also drops any queued files, recorded errors and hot reload state
+    #[allow(unused)]
+    pub fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.entry.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                slot.bytes = 0;
+                self.free_slots.push(index as u32);
+            }
+        }
+        self.handles.clear();
+        self.load_order.clear();
+        self.memory_used = 0;
+        self.aliases.clear();
+        self.tags.clear();
+        self.animations.clear();
+        self.errors.clear();
+        self.pending.clear();
+        self.progress = LoadProgress::default();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(hot_reload) = &mut self.hot_reload {
+            hot_reload.modified.clear();
+        }
+        // Start the atlas over so its pages are freed along with the entries
+        let fresh_atlas = self.atlas.as_ref().map(|atlas| TextureAtlas::new(atlas.page_size()));
+        self.replace_atlas(fresh_atlas);
+    }
+    
+    /// Load a texture from disk again, replacing the stored copy but keeping its handle
+    /// The mask is rebuilt with the settings it was first loaded with
+    /// Returns NotFound for paths that are not loaded (use preload for those),
+    /// and for images added with insert_image, which have no file behind them
+    #[allow(unused)]
+    pub async fn reload(&mut self, path: &str) -> Result<TextureHandle, ImageError> {
+        let path = self.resolve(path).to_string(); // Owned so the alias map can be borrowed mutably below
+        let Some(handle) = self.handles.get(&path) else {
+            return Err(ImageError::NotFound(path));
+        };
+        let mask_settings = self.slots[handle.index as usize].mask_settings;
+        self.reload_with_mask(&path, mask_settings).await
+    }
+    
+    /// Load a texture from disk again, rebuilding its mask with new settings
+    async fn reload_with_mask(&mut self, path: &str, mask_settings: MaskSettings) -> Result<TextureHandle, ImageError> {
+        let path = path.to_string();
+        // Animations reload every frame and return the handle of the first one
+        if self.animations.contains_key(&path) {
+            let frames = self.load_and_store_animation(&path, mask_settings).await?;
+            return frames.first().map(|(handle, _)| *handle).ok_or(ImageError::ZeroSize(path));
+        }
+        let (handle, _) = self.load_and_store(&path, mask_settings).await?;
+        Ok(handle)
+    }
+    
+    /// Choose how a loaded texture is sampled when drawn scaled (Nearest keeps pixel art sharp)
+    /// The filter sticks to the entry, so reload and hot reload apply it to the new texture too
+    /// Atlas entries share their page texture, so this changes every entry on the same page
+    /// Returns false if the path is not loaded
+    #[allow(unused)]
+    pub fn set_filter(&mut self, path: &str, filter: FilterMode) -> bool {
+        let Some(handle) = self.get_handle(path) else {
+            return false;
+        };
+        let slot = &mut self.slots[handle.index as usize];
+        slot.filter = Some(filter);
+        if let Some((texture, _, _, _)) = &slot.entry {
+            texture.set_filter(filter);
+        }
+        true
+    }
+    
+    /// Give a loaded path a short name that works with get_preload, get_handle, unload and reload
+    #[allow(unused)]
+    pub fn add_alias(&mut self, alias: &str, path: &str) {
+        self.aliases.insert(alias.to_string(), path.to_string());
+    }
+    
+    /// Get handles for every loaded texture listed under a manifest group or tag
+    #[allow(unused)]
+    pub fn get_group(&self, tag: &str) -> Vec<TextureHandle> {
+        self.tags
+            .get(tag)
+            .map(|paths| paths.iter().filter_map(|path| self.get_handle(path)).collect())
+            .unwrap_or_default()
+    }
+    
+    /// Load every image listed in a JSON manifest file
+    /// Returns the handles in the order the manifest lists them
+    #[allow(unused)]
+    pub async fn load_manifest(&mut self, manifest_path: &str) -> Result<Vec<TextureHandle>, ManifestError> {
+        let text = macroquad::file::load_string(manifest_path)
+            .await
+            .map_err(|_| ManifestError::NotFound(manifest_path.to_string()))?;
+        self.load_manifest_str(manifest_path, &text).await
+    }
+    
+    /// Load every image listed in manifest JSON that is already in memory
+    /// The name is only used in error messages
+    /// Stops at the first image that fails, unless fallback is enabled
+    pub async fn load_manifest_str(&mut self, name: &str, text: &str) -> Result<Vec<TextureHandle>, ManifestError> {
+        let manifest: Manifest = serde_json::from_str(text).map_err(|err| ManifestError::Parse {
+            path: name.to_string(),
+            message: err.to_string(),
+        })?;
+        let mut handles = Vec::with_capacity(manifest.assets.len());
+        for asset in manifest.assets {
+            let manifest_settings = asset.mask_settings();
+            let mask_settings = manifest_settings.unwrap_or_default();
+            let handle = if self.fallback_enabled {
+                self.preload_with_mask(&asset.path, mask_settings).await
+            } else {
+                self.try_preload_with_mask(&asset.path, mask_settings)
+                    .await
+                    .map_err(ManifestError::Image)?
+            };
+            // A path loaded earlier with other mask settings is read again so the manifest's settings win
+            if let Some(wanted) = manifest_settings.filter(|wanted| self.slots[handle.index as usize].mask_settings != *wanted) {
+                if let Err(err) = self.reload_with_mask(&asset.path, wanted).await {
+                    if !self.fallback_enabled {
+                        return Err(ManifestError::Image(err));
+                    }
+                    self.errors.push(err);
+                }
+            }
+            if let Some(filter) = asset.filter_mode() {
+                self.set_filter(&asset.path, filter);
+            }
+            if let Some(alias) = &asset.alias {
+                self.add_alias(alias, &asset.path);
+            }
+            for tag in asset.tags {
+                let paths = self.tags.entry(tag).or_default();
+                if !paths.contains(&asset.path) {
+                    paths.push(asset.path.clone());
+                }
+            }
+            handles.push(handle);
+        }
+        Ok(handles)
+    }
+    
+    /// Preload every file in a folder whose name matches the pattern, in natural sort order
+    /// (so img2.png comes before img10.png). The pattern supports * and ? and alternatives
+    /// separated by |, for example "*.png|*.jpg". Not available in wasm builds, which cannot list folders
+    /// Stops at the first image that fails, unless fallback is enabled
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(unused)]
+    pub async fn preload_dir(&mut self, dir: &str, pattern: &str) -> Result<Vec<TextureHandle>, ImageError> {
+        let paths = list_dir(dir, pattern)?;
+        let mut handles = Vec::with_capacity(paths.len());
+        for path in paths {
+            let handle = if self.fallback_enabled {
+                self.preload(&path).await
+            } else {
+                self.try_preload(&path).await?
+            };
+            handles.push(handle);
+        }
+        Ok(handles)
+    }
+    
+    /// Get handles for every loaded texture whose path matches a glob pattern, in load order
+    /// Uses the same pattern rules as preload_dir, matched against the whole path
+    #[allow(unused)]
+    pub fn get_matching(&self, pattern: &str) -> Vec<TextureHandle> {
+        self.load_order
+            .iter()
+            .filter(|path| glob_match(pattern, path))
+            .filter_map(|path| self.get_handle(path))
+            .collect()
+    }
+    
+    /// Watch loaded files for changes on disk, checking every interval seconds (native builds only)
+    /// Call update_hot_reload once per frame to reload changed files
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(unused)]
+    pub fn enable_hot_reload(&mut self, interval: f64) {
+        let mut hot_reload = HotReload {
+            interval,
+            last_check: macroquad::time::get_time(),
+            modified: HashMap::new(),
+        };
+        // Remember the current times so files are only reloaded after they change
+        // Placeholders for missing files are left out so they load on the first check
+        for path in &self.load_order {
+            if is_placeholder(&self.handles, &self.slots, path) {
+                continue;
+            }
+            if let Some(time) = modified_time(path) {
+                hot_reload.modified.insert(path.clone(), time);
+            }
+        }
+        self.hot_reload = Some(hot_reload);
+    }
+    
+    /// Stop watching files for changes
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(unused)]
+    pub fn disable_hot_reload(&mut self) {
+        self.hot_reload = None;
+    }
+    
+    /// Reload any loaded file whose modification time changed since the last check,
+    /// and any missing file placeholder (see enable_fallback) whose file now exists
+    /// Does nothing until the interval has passed, so it is cheap to call every frame
+    /// Only StillImages set with set_handle (and AnimatedImages built with from_handles) pick up
+    /// the new texture and mask, when their refresh() is called. Images set with set_preload,
+    /// sprite sheets and AnimatedImage::from_frames keep showing the old texture
+    /// Files that fail to load (for example while still being saved) are retried next check
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(unused)]
+    pub async fn update_hot_reload(&mut self) -> Vec<AssetChange> {
+        let now = macroquad::time::get_time();
+        let changed: Vec<String> = match &mut self.hot_reload {
+            Some(hot_reload) if now - hot_reload.last_check >= hot_reload.interval => {
+                hot_reload.last_check = now;
+                let mut changed = Vec::new();
+                for path in &self.load_order {
+                    // Images added with insert_image have no file and are skipped here
+                    let Some(time) = modified_time(path) else {
+                        continue;
+                    };
+                    match hot_reload.modified.get(path) {
+                        Some(&previous) if previous != time => changed.push(path.clone()),
+                        Some(_) => {}
+                        // A missing file placeholder is loaded as soon as its file turns up
+                        // (its time is recorded once the reload succeeds)
+                        None if is_placeholder(&self.handles, &self.slots, path) => changed.push(path.clone()),
+                        None => {
+                            // First time this file has been seen, start watching it
+                            hot_reload.modified.insert(path.clone(), time);
+                        }
+                    }
+                }
+                changed
+            }
+            _ => return Vec::new(),
+        };
+        
+        let mut changes = Vec::with_capacity(changed.len());
+        for path in changed {
+            let result = self.reload(&path).await;
+            if result.is_ok() {
+                if let (Some(hot_reload), Some(time)) = (&mut self.hot_reload, modified_time(&path)) {
+                    hot_reload.modified.insert(path.clone(), time);
+                }
+            }
+            changes.push(AssetChange { path, result });
+        }
+        changes
+    }
+    
+    /// Pack images loaded from now on into shared atlas pages of page_size x page_size pixels
+    /// Fewer textures means fewer texture switches when drawing many StillImages
+    /// page_size is capped at MAX_PAGE_SIZE (8192). Space freed by unload, reload or eviction is reused,
+    /// and a page is freed once nothing is left on it. A manifest filter mode applies to the whole page
+    #[allow(unused)]
+    pub fn enable_atlas(&mut self, page_size: u32) {
+        let page_size = page_size.min(MAX_PAGE_SIZE);
+        if self.atlas.as_ref().map(|atlas| atlas.page_size()) != Some(page_size) {
+            self.replace_atlas(Some(TextureAtlas::new(page_size)));
+        }
+    }
+    
+    /// Stop packing new images. Images already packed keep using their atlas pages
+    #[allow(unused)]
+    pub fn disable_atlas(&mut self) {
+        self.replace_atlas(None);
+    }
+    
+    /// Check if atlas mode is on
+    #[allow(unused)]
+    pub fn is_atlas_enabled(&self) -> bool {
+        self.atlas.is_some()
+    }
+    
+    /// Number of atlas pages currently holding images
+    #[allow(unused)]
+    pub fn atlas_page_count(&self) -> usize {
+        self.atlas.as_ref().map_or(0, |atlas| atlas.page_count())
+    }
+    
+    /// Limit approximate texture memory (width * height * 4 plus the mask, in bytes)
+    /// When a new texture pushes usage over the limit the least recently used ones are unloaded
+    /// Pass None to remove the limit
+    #[allow(unused)]
+    pub fn set_memory_budget(&mut self, budget: Option<usize>) {
+        self.memory_budget = budget;
+        self.enforce_budget(&[]);
+    }
+    
+    /// Get the current memory budget in bytes, if any
+    #[allow(unused)]
+    pub fn get_memory_budget(&self) -> Option<usize> {
+        self.memory_budget
+    }
+    
+    /// Approximate memory used by all loaded textures and masks, in bytes
+    #[allow(unused)]
+    pub fn memory_usage(&self) -> usize {
+        self.memory_used
+    }
+    
+    /// Turn on the missing texture fallback for preload and preload_all
+    #[allow(unused)]
+    pub fn enable_fallback(&mut self) {
+        self.fallback_enabled = true;
+    }
+    
+    /// Turn off the missing texture fallback so failed preloads panic again
+    #[allow(unused)]
+    pub fn disable_fallback(&mut self) {
+        self.fallback_enabled = false;
+    }
+    
+    /// Check if the missing texture fallback is on
+    #[allow(unused)]
+    pub fn is_fallback_enabled(&self) -> bool {
+        self.fallback_enabled
+    }
+    
+    /// Get every load failure that was replaced by the placeholder, in the order they happened
+    #[allow(unused)]
+    pub fn get_errors(&self) -> &[ImageError] {
+        &self.errors
+    }
+    
+    /// Forget the recorded load failures
+    #[allow(unused)]
+    pub fn clear_errors(&mut self) {
+        self.errors.clear();
+    }
+    
     /// Get the number of preloaded textures
     #[allow(unused)]
     pub fn texture_count(&self) -> usize {
@@ -130,4 +1324,362 @@ impl TextureManager {
     pub fn get_texture_paths(&self) -> &[String] {
         &self.load_order
     }
-}
\ No newline at end of file
+}
+
+/// True if a path is stored as the missing file placeholder
+#[cfg(not(target_arch = "wasm32"))]
+fn is_placeholder(handles: &HashMap<String, TextureHandle>, slots: &[TextureSlot], path: &str) -> bool {
+    handles
+        .get(path)
+        .and_then(|handle| slots[handle.index as usize].entry.as_ref())
+        .is_some_and(|(_, _, filename, _)| filename.starts_with(crate::modules::still_image::MISSING_PREFIX))
+}
+
+/// Last modification time of a file, or None if it can't be read
+#[cfg(not(target_arch = "wasm32"))]
+fn modified_time(path: &str) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Check a name against a glob pattern with * (any run of characters) and ? (any one character)
+/// Several patterns can be given separated by |, and the name matches if any of them do
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    pattern.split('|').any(|alternative| glob_match_one(alternative.trim(), name))
+}
+
+fn glob_match_one(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None; // Pattern index after the last *, and the name index it matched from
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p + 1, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // Let the last * swallow one more character and try again
+            p = star_p;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Paths of the files in a folder whose names match a glob pattern, in natural sort order
+/// This is the order preload_dir loads them in
+#[cfg(not(target_arch = "wasm32"))]
+pub fn list_dir(dir: &str, pattern: &str) -> Result<Vec<String>, ImageError> {
+    let entries = std::fs::read_dir(dir).map_err(|err| convert_io_error(dir, err))?;
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| glob_match(pattern, name))
+        .collect();
+    names.sort_by(|a, b| natural_cmp(a, b));
+    Ok(names
+        .into_iter()
+        .map(|name| format!("{}/{}", dir.trim_end_matches('/'), name))
+        .collect())
+}
+
+/// Compare two names so that runs of digits are ordered by their numeric value
+/// This puts img2.png before img10.png, unlike a plain string sort
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    let mut case_tiebreak = Ordering::Equal; // First upper/lower case difference, only used if all else is equal
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return case_tiebreak,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_digits = take_digits(&mut a_chars);
+                let b_digits = take_digits(&mut b_chars);
+                // Compare by value first (ignoring leading zeros), then by length so "01" and "1" stay stable
+                let a_trimmed = a_digits.trim_start_matches('0');
+                let b_trimmed = b_digits.trim_start_matches('0');
+                let ordering = a_trimmed
+                    .len()
+                    .cmp(&b_trimmed.len())
+                    .then_with(|| a_trimmed.cmp(b_trimmed))
+                    .then_with(|| a_digits.len().cmp(&b_digits.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_ascii_lowercase().cmp(&y.to_ascii_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                if case_tiebreak == Ordering::Equal {
+                    case_tiebreak = x.cmp(&y);
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Pull a run of ASCII digits off the front of an iterator
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run a future that finishes without waiting on anything (no file or GPU access)
+    fn run_now<F: std::future::Future>(future: F) -> F::Output {
+        let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+        match std::pin::pin!(future).poll(&mut context) {
+            std::task::Poll::Ready(output) => output,
+            std::task::Poll::Pending => panic!("future did not finish immediately"),
+        }
+    }
+
+    // An entry whose texture is a bare id, so it can be stored and dropped without a window
+    fn fake_entry(name: &str) -> PreloadedTexture {
+        use macroquad::miniquad::{RawId, TextureId};
+        let texture = Texture2D::from_miniquad_texture(TextureId::from_raw_id(RawId::OpenGl(0)));
+        (texture, Rc::new(TransparencyMask::filled(4, 4)), name.into(), None)
+    }
+
+    #[test]
+    fn manifest_fields_default_to_none() {
+        let manifest: Manifest = serde_json::from_str(r#"{ "assets": [{ "path": "assets/a.png" }] }"#).unwrap();
+        let asset = &manifest.assets[0];
+        assert_eq!(asset.path, "assets/a.png");
+        assert_eq!(asset.alias, None);
+        assert!(asset.tags.is_empty());
+        assert_eq!(asset.filter_mode(), None);
+        assert_eq!(asset.mask_settings(), None);
+    }
+
+    #[test]
+    fn manifest_mask_fields_fill_in_defaults() {
+        let text = r#"{ "assets": [
+            { "path": "a.png", "alpha_threshold": 128 },
+            { "path": "b.png", "mask_mode": "alpha" },
+            { "path": "c.png", "filter": "nearest" }
+        ] }"#;
+        let manifest: Manifest = serde_json::from_str(text).unwrap();
+        assert_eq!(manifest.assets[0].mask_settings(), Some(MaskSettings::threshold(128)));
+        assert_eq!(
+            manifest.assets[1].mask_settings(),
+            Some(MaskSettings {
+                alpha_threshold: 0,
+                mode: MaskMode::Alpha,
+            })
+        );
+        assert_eq!(manifest.assets[2].mask_settings(), None);
+        assert_eq!(manifest.assets[2].filter_mode(), Some(FilterMode::Nearest));
+    }
+
+    #[test]
+    fn manifest_unknown_field_is_a_parse_error() {
+        let mut texture_manager = TextureManager::new();
+        let text = r#"{ "assets": [{ "path": "assets/a.png", "colour": "red" }] }"#;
+        match run_now(texture_manager.load_manifest_str("manifest.json", text)) {
+            Err(ManifestError::Parse { path, message }) => {
+                assert_eq!(path, "manifest.json");
+                assert!(message.contains("colour"), "{message}");
+            }
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+        let text = r#"{ "assets": [], "version": 2 }"#;
+        assert!(matches!(run_now(texture_manager.load_manifest_str("manifest.json", text)), Err(ManifestError::Parse { .. })));
+        assert_eq!(texture_manager.texture_count(), 0);
+    }
+
+    #[test]
+    fn manifest_aliases_and_tags_resolve() {
+        let mut texture_manager = TextureManager::new();
+        let hero = texture_manager.store("assets/hero.png", fake_entry("assets/hero.png"), MaskSettings::default());
+        let tree = texture_manager.store("assets/tree.png", fake_entry("assets/tree.png"), MaskSettings::default());
+        let text = r#"{ "assets": [
+            { "path": "assets/hero.png", "alias": "hero", "tags": ["characters", "all"] },
+            { "path": "assets/tree.png", "alias": "tree", "tags": ["all"] }
+        ] }"#;
+        let handles = run_now(texture_manager.load_manifest_str("manifest.json", text)).unwrap();
+        assert_eq!(handles, vec![hero, tree]);
+        assert_eq!(texture_manager.get_handle("hero"), Some(hero));
+        assert_eq!(texture_manager.get_handle("tree"), Some(tree));
+        assert_eq!(texture_manager.get_group("characters"), vec![hero]);
+        assert_eq!(texture_manager.get_group("all"), vec![hero, tree]);
+        assert!(texture_manager.get_group("scenery").is_empty());
+        // Listing the same tag again does not add the path twice
+        run_now(texture_manager.load_manifest_str("manifest.json", text)).unwrap();
+        assert_eq!(texture_manager.get_group("all"), vec![hero, tree]);
+        assert_eq!(texture_manager.texture_count(), 2);
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn placeholders_are_spotted_for_hot_reload() {
+        let mut texture_manager = TextureManager::new();
+        texture_manager.store("assets/real.png", fake_entry("assets/real.png"), MaskSettings::default());
+        let placeholder = format!("{}assets/later.png", crate::modules::still_image::MISSING_PREFIX);
+        texture_manager.store("assets/later.png", fake_entry(&placeholder), MaskSettings::default());
+        assert!(!is_placeholder(&texture_manager.handles, &texture_manager.slots, "assets/real.png"));
+        assert!(is_placeholder(&texture_manager.handles, &texture_manager.slots, "assets/later.png"));
+        assert!(!is_placeholder(&texture_manager.handles, &texture_manager.slots, "assets/unknown.png"));
+    }
+
+    #[test]
+    fn embedded_assets_lists_the_assets_folder() {
+        let assets: &[(&str, &[u8])] = crate::embedded_assets!();
+        let names: Vec<&str> = assets.iter().map(|(name, _)| *name).collect();
+        // manifest.json is not an image, so build.rs leaves it out
+        assert_eq!(names, ["assets/image1.png", "assets/image2.png"]);
+        for (name, bytes) in assets {
+            let image = decode_image(name, bytes).unwrap();
+            assert!(image.width > 0 && image.height > 0, "{name} decoded to an empty image");
+        }
+    }
+
+    #[test]
+    fn embed_assets_includes_the_named_files() {
+        let assets: &[(&str, &[u8])] = crate::embed_assets!["assets/image1.png"];
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].0, "assets/image1.png");
+        assert_eq!(assets[0].1, std::fs::read("assets/image1.png").unwrap().as_slice());
+        let image = decode_image(assets[0].0, assets[0].1).unwrap();
+        assert_eq!(image.bytes.len(), image.width as usize * image.height as usize * 4);
+    }
+
+    #[test]
+    fn budget_keeps_every_handle_asked_for() {
+        let mut texture_manager = TextureManager::new();
+        let older = texture_manager.store("assets/older.png", fake_entry("assets/older.png"), MaskSettings::default());
+        let frame0 = texture_manager.store("anim.gif", fake_entry("anim.gif"), MaskSettings::default());
+        let frame1 = texture_manager.store_packed("anim.gif#1", fake_entry("anim.gif#1"), MaskSettings::default(), None, false);
+        // Set directly so setting it doesn't already evict
+        texture_manager.memory_budget = Some(0);
+        texture_manager.enforce_budget(&[frame0, frame1]);
+        assert!(!texture_manager.is_valid(older));
+        assert!(texture_manager.is_valid(frame0));
+        assert!(texture_manager.is_valid(frame1));
+        assert_eq!(texture_manager.get_texture_paths(), ["anim.gif"]);
+    }
+
+    #[test]
+    fn reload_unknown_path_is_not_found() {
+        let mut texture_manager = TextureManager::new();
+        let result = run_now(texture_manager.reload("assets/never_loaded.png"));
+        assert_eq!(result, Err(ImageError::NotFound("assets/never_loaded.png".to_string())));
+        assert_eq!(texture_manager.texture_count(), 0);
+    }
+
+    #[test]
+    fn clear_drops_the_load_queue() {
+        let mut texture_manager = TextureManager::new();
+        texture_manager.queue_all(&["assets/a.png", "assets/b.png"]);
+        assert!(texture_manager.is_loading());
+        texture_manager.clear();
+        assert!(!texture_manager.is_loading());
+        assert_eq!(texture_manager.progress(), &LoadProgress::default());
+        assert!(run_now(texture_manager.load_next()).is_none());
+    }
+
+    #[test]
+    fn natural_cmp_orders_numbers_by_value() {
+        assert_eq!(natural_cmp("img2.png", "img10.png"), Ordering::Less);
+        assert_eq!(natural_cmp("img10.png", "img2.png"), Ordering::Greater);
+        assert_eq!(natural_cmp("img9", "img10"), Ordering::Less);
+        assert_eq!(natural_cmp("a1b2", "a1b10"), Ordering::Less);
+        assert_eq!(natural_cmp("img2.png", "img2.png"), Ordering::Equal);
+        assert_eq!(natural_cmp("img", "img1"), Ordering::Less);
+    }
+
+    #[test]
+    fn natural_cmp_leading_zeros() {
+        // Same value: the shorter spelling first, so the order is still total
+        assert_eq!(natural_cmp("img1", "img01"), Ordering::Less);
+        assert_eq!(natural_cmp("img001", "img01"), Ordering::Greater);
+        // Value wins over length
+        assert_eq!(natural_cmp("img002", "img10"), Ordering::Less);
+        assert_eq!(natural_cmp("frame_0009", "frame_0010"), Ordering::Less);
+    }
+
+    #[test]
+    fn natural_cmp_ignores_case_until_tied() {
+        assert_eq!(natural_cmp("apple.png", "Banana.png"), Ordering::Less);
+        assert_eq!(natural_cmp("Apple.png", "banana.png"), Ordering::Less);
+        // Only the case differs, upper case first
+        assert_eq!(natural_cmp("Img1.png", "img1.png"), Ordering::Less);
+        assert_eq!(natural_cmp("img1.png", "Img1.png"), Ordering::Greater);
+        // A later difference still beats an earlier case difference
+        assert_eq!(natural_cmp("img2.png", "Img10.png"), Ordering::Less);
+    }
+
+    #[test]
+    fn glob_match_alternatives() {
+        assert!(glob_match("*.png|*.jpg", "hero.png"));
+        assert!(glob_match("*.png|*.jpg", "photo.jpg"));
+        assert!(glob_match("*.png | *.jpg", "photo.jpg"));
+        assert!(!glob_match("*.png|*.jpg", "notes.txt"));
+        assert!(!glob_match("*.png|*.jpg", "hero.png.bak"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("assets/*.png", "assets/hero.png"));
+    }
+
+    #[test]
+    fn glob_match_question_mark() {
+        assert!(glob_match("img?.png", "img1.png"));
+        assert!(glob_match("img?.png", "imgA.png"));
+        assert!(!glob_match("img?.png", "img.png"));
+        assert!(!glob_match("img?.png", "img10.png"));
+        assert!(glob_match("img??.png", "img10.png"));
+        assert!(glob_match("*_?.png", "walk_3.png"));
+    }
+
+    #[test]
+    fn list_dir_natural_order() {
+        let dir = std::env::temp_dir().join(format!("image_test_list_dir_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub.png")).unwrap();
+        let names = ["img10.png", "img2.png", "IMG1.png", "img1.png", "photo.jpg", "notes.txt", "img02.png"];
+        let pixels = image::RgbImage::from_pixel(2, 2, image::Rgb([0, 0, 255]));
+        for name in names {
+            if name.ends_with(".txt") {
+                std::fs::write(dir.join(name), b"not an image").unwrap();
+            } else {
+                pixels.save(dir.join(name)).unwrap(); // Format from the extension
+            }
+        }
+        let dir_str = dir.to_str().unwrap().to_string();
+        let listed = list_dir(&dir_str, "*.png|*.jpg").unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let expected: Vec<String> = ["IMG1.png", "img1.png", "img2.png", "img02.png", "img10.png", "photo.jpg"]
+            .iter()
+            .map(|name| format!("{}/{}", dir_str, name))
+            .collect();
+        assert_eq!(listed, expected);
+    }
+
+    #[test]
+    fn list_dir_missing_folder() {
+        let missing = std::env::temp_dir().join("image_test_no_such_folder");
+        let missing = missing.to_str().unwrap();
+        assert_eq!(list_dir(missing, "*"), Err(ImageError::NotFound(missing.to_string())));
+    }
+}
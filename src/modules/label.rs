@@ -384,6 +384,7 @@ fn draw_rectangle_border(x: f32, y: f32, w: f32, h: f32, thickness: f32, color:
 }
 
 // New function to draw rounded rectangle borders with consistent thickness
+#[allow(clippy::too_many_arguments)]
 fn draw_round_rect_border(x: f32, y: f32, w: f32, h: f32, radius: f32, thickness: f32, color: Color, bg_color: Color) {
     if radius <= 0.0 {
         // Use our new rectangle border function for non-rounded corners
@@ -0,0 +1,138 @@
+/*
+Made by: image_test contributors
+Date: 2026-10-18
+Program Details: Ready-made loading screen that shows TextureManager progress
+
+To import you need:
+In the mod modules section add:
+    pub mod loading_screen;
+    pub mod label;
+    pub mod image_preload;
+
+Then add the following with the use commands:
+use crate::modules::loading_screen::LoadingScreen;
+
+Queue your textures, then let the loading screen work through them.
+It loads one file per frame and draws a progress bar in between, so the
+window (or browser tab in the wasm build) never looks frozen:
+    texture_manager.queue_all(&["assets/image1.png", "assets/image2.png"]);
+    LoadingScreen::new("Loading...").run(&mut texture_manager).await;
+
+You can change the colors and the size of the bar:
+    let mut loading = LoadingScreen::new("Loading assets");
+    loading
+        .with_colors(BLACK, SKYBLUE, DARKGRAY)  // background, bar, empty bar
+        .with_bar_size(400.0, 24.0);
+    loading.run(&mut texture_manager).await;
+
+If you want to drive the loading yourself, call draw() each frame instead:
+    while texture_manager.is_loading() {
+        texture_manager.load_next().await;
+        loading.draw(texture_manager.progress());
+        next_frame().await;
+    }
+*/
+use macroquad::prelude::*;
+use crate::modules::image_preload::{LoadProgress, TextureManager};
+use crate::modules::label::{Label, TextAlign};
+
+#[allow(unused)]
+pub struct LoadingScreen {
+    title: Label,
+    status: Label, // Shows the count and the file being loaded
+    background: Color,
+    bar_color: Color,
+    bar_background: Color,
+    bar_width: f32,
+    bar_height: f32,
+}
+
+impl LoadingScreen {
+    // Create a loading screen with the given title text
+    #[allow(unused)]
+    pub fn new(title: &str) -> Self {
+        let mut title_label = Label::new(title, 0.0, 0.0, 32);
+        title_label
+            .with_colors(WHITE, Some(DARKGRAY))
+            .with_round(8.0)
+            .with_border(GRAY, 1.5)
+            .with_fixed_size(400.0, 44.0)
+            .with_alignment(TextAlign::Center);
+
+        let mut status_label = Label::new("", 0.0, 0.0, 20);
+        status_label
+            .with_colors(LIGHTGRAY, None)
+            .with_fixed_size(400.0, 24.0)
+            .with_alignment(TextAlign::Center);
+
+        Self {
+            title: title_label,
+            status: status_label,
+            background: BLACK,
+            bar_color: GREEN,
+            bar_background: DARKGRAY,
+            bar_width: 400.0,
+            bar_height: 24.0,
+        }
+    }
+
+    // Set the screen background, filled bar and empty bar colors
+    #[allow(unused)]
+    pub fn with_colors(&mut self, background: Color, bar_color: Color, bar_background: Color) -> &mut Self {
+        self.background = background;
+        self.bar_color = bar_color;
+        self.bar_background = bar_background;
+        self
+    }
+
+    // Set the size of the progress bar in pixels
+    #[allow(unused)]
+    pub fn with_bar_size(&mut self, width: f32, height: f32) -> &mut Self {
+        self.bar_width = width;
+        self.bar_height = height;
+        self.title.with_fixed_size(width, 44.0);
+        self.status.with_fixed_size(width, 24.0);
+        self
+    }
+
+    // Draw one frame of the loading screen for the given progress
+    #[allow(unused)]
+    pub fn draw(&mut self, progress: &LoadProgress) {
+        clear_background(self.background);
+
+        // Centre everything on the current window size so resizing works while loading
+        let bar_x = screen_width() / 2.0 - self.bar_width / 2.0;
+        let bar_y = screen_height() / 2.0 - self.bar_height / 2.0;
+
+        // Labels draw their text baseline at y, so offset by the font size to sit above the bar
+        self.title.set_position(bar_x + 5.0, bar_y - 30.0);
+        self.title.draw();
+
+        draw_rectangle(bar_x, bar_y, self.bar_width, self.bar_height, self.bar_background);
+        draw_rectangle(bar_x, bar_y, self.bar_width * progress.fraction(), self.bar_height, self.bar_color);
+        draw_rectangle_lines(bar_x, bar_y, self.bar_width, self.bar_height, 2.0, WHITE);
+
+        let status = match &progress.current {
+            Some(path) => format!("{} / {}  {}", progress.loaded, progress.total, path),
+            None => format!("{} / {}", progress.loaded, progress.total),
+        };
+        self.status.set_text(status);
+        self.status.set_position(bar_x + 5.0, bar_y + self.bar_height + 30.0);
+        self.status.draw();
+    }
+
+    // Load every queued texture, drawing the loading screen between files
+    // Returns once the TextureManager queue is empty
+    #[allow(unused)]
+    pub async fn run(&mut self, texture_manager: &mut TextureManager) {
+        // Show the empty bar before the first file starts
+        self.draw(texture_manager.progress());
+        next_frame().await;
+
+        while texture_manager.is_loading() {
+            texture_manager.load_next().await;
+            self.draw(texture_manager.progress());
+            next_frame().await;
+        }
+    }
+}
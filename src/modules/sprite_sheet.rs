@@ -0,0 +1,146 @@
+/*
+Made by: image_test contributors
+Date: 2026-10-18
+Program Details: Splits one preloaded texture into many frames for StillImage
+
+To import you need:
+In the mod modules section add:
+    pub mod sprite_sheet;
+    pub mod still_image;
+    pub mod image_preload;
+
+Then add the following with the use commands:
+use crate::modules::sprite_sheet::SpriteSheet;
+
+Cut a sheet into equal cells, read left to right then top to bottom:
+    let sheet = SpriteSheet::from_grid(texture_manager.get_preload("assets/hero.png").unwrap(), 32.0, 48.0);
+    img.set_sprite(&sheet, 3); // Show the fourth frame
+
+Or list the frames yourself when they are different sizes:
+    let sheet = SpriteSheet::from_rects(
+        texture_manager.get_preload("assets/items.png").unwrap(),
+        vec![Rect::new(0.0, 0.0, 16.0, 16.0), Rect::new(16.0, 0.0, 32.0, 16.0)],
+    );
+
+Many StillImages can share one sheet; the texture and mask are never copied.
+Rectangles are in image pixels, so sheets work the same when the texture was packed into an atlas.
+Hit-testing and collisions only use the part of the mask under the current frame.
+*/
+use macroquad::prelude::*;
+use crate::modules::image_preload::PreloadedTexture;
+
+pub struct SpriteSheet {
+    preloaded: PreloadedTexture,
+    frames: Vec<Rect>, // Source rectangles in image pixels, in frame order
+}
+
+impl SpriteSheet {
+    // Split an image into cells of the given size, row by row
+    // Partial cells at the right and bottom edges are left out
+    #[allow(unused)]
+    pub fn from_grid(preloaded: PreloadedTexture, cell_width: f32, cell_height: f32) -> Self {
+        Self::from_grid_spaced(preloaded, cell_width, cell_height, 0.0, 0.0)
+    }
+
+    // Split an image into cells with a border of margin pixels around the sheet
+    // and spacing pixels between neighbouring cells
+    // Negative margin or spacing counts as 0, and non-finite sizes give an empty sheet
+    #[allow(unused)]
+    pub fn from_grid_spaced(preloaded: PreloadedTexture, cell_width: f32, cell_height: f32, margin: f32, spacing: f32) -> Self {
+        // The mask always covers the whole image, even for atlas entries
+        let frames = grid_cells(preloaded.1.width() as f32, preloaded.1.height() as f32, cell_width, cell_height, margin, spacing);
+        Self { preloaded, frames }
+    }
+
+    // Use an explicit list of frame rectangles
+    #[allow(unused)]
+    pub fn from_rects(preloaded: PreloadedTexture, frames: Vec<Rect>) -> Self {
+        Self { preloaded, frames }
+    }
+
+    // The shared texture, mask and filename, ready for StillImage::set_preload
+    pub fn preloaded(&self) -> PreloadedTexture {
+        self.preloaded.clone()
+    }
+
+    // Source rectangle of one frame, or None if the index is out of range
+    pub fn frame(&self, index: usize) -> Option<Rect> {
+        self.frames.get(index).copied()
+    }
+
+    #[allow(unused)]
+    pub fn frames(&self) -> &[Rect] {
+        &self.frames
+    }
+
+    #[allow(unused)]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+// Cells of a grid over an image_width x image_height image, row by row
+fn grid_cells(image_width: f32, image_height: f32, cell_width: f32, cell_height: f32, margin: f32, spacing: f32) -> Vec<Rect> {
+    let mut frames = Vec::new();
+    if ![cell_width, cell_height, margin, spacing].iter().all(|value| value.is_finite()) || cell_width < 1.0 || cell_height < 1.0 {
+        return frames;
+    }
+    // Clamped so every step moves forward by at least a whole cell
+    let margin = margin.max(0.0);
+    let spacing = spacing.max(0.0);
+    let mut y = margin;
+    while y + cell_height <= image_height - margin {
+        let mut x = margin;
+        while x + cell_width <= image_width - margin {
+            frames.push(Rect::new(x, y, cell_width, cell_height));
+            x += cell_width + spacing;
+        }
+        y += cell_height + spacing;
+    }
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_cells_row_by_row() {
+        let cells = grid_cells(64.0, 32.0, 32.0, 16.0, 0.0, 0.0);
+        assert_eq!(
+            cells,
+            vec![
+                Rect::new(0.0, 0.0, 32.0, 16.0),
+                Rect::new(32.0, 0.0, 32.0, 16.0),
+                Rect::new(0.0, 16.0, 32.0, 16.0),
+                Rect::new(32.0, 16.0, 32.0, 16.0),
+            ]
+        );
+        // Partial cells at the edges are left out
+        assert_eq!(grid_cells(70.0, 20.0, 32.0, 16.0, 0.0, 0.0).len(), 2);
+    }
+
+    #[test]
+    fn grid_cells_margin_and_spacing() {
+        let cells = grid_cells(40.0, 20.0, 16.0, 16.0, 2.0, 4.0);
+        assert_eq!(cells, vec![Rect::new(2.0, 2.0, 16.0, 16.0), Rect::new(22.0, 2.0, 16.0, 16.0)]);
+    }
+
+    #[test]
+    fn grid_cells_negative_margin_and_spacing_count_as_zero() {
+        // Spacing of minus a whole cell used to stop x from advancing
+        assert_eq!(grid_cells(64.0, 16.0, 16.0, 16.0, 0.0, -16.0), grid_cells(64.0, 16.0, 16.0, 16.0, 0.0, 0.0));
+        assert_eq!(grid_cells(64.0, 16.0, 16.0, 16.0, -100.0, -1000.0).len(), 4);
+    }
+
+    #[test]
+    fn grid_cells_non_finite_input_is_empty() {
+        for bad in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert!(grid_cells(64.0, 64.0, bad, 16.0, 0.0, 0.0).is_empty());
+            assert!(grid_cells(64.0, 64.0, 16.0, bad, 0.0, 0.0).is_empty());
+            assert!(grid_cells(64.0, 64.0, 16.0, 16.0, bad, 0.0).is_empty());
+            assert!(grid_cells(64.0, 64.0, 16.0, 16.0, 0.0, bad).is_empty());
+        }
+        assert!(grid_cells(64.0, 64.0, 0.5, 16.0, 0.0, 0.0).is_empty());
+    }
+}
@@ -6,6 +6,7 @@ Program Details: Image module for displaying and manipulating images
 To import you need:
 In the mod modules section add:
     pub mod still_image;
+    pub mod transparency_mask;
     
 Then add the following with the use commands:
 use crate::modules::still_image::StillImage;
@@ -36,6 +37,12 @@ Usage examples:
     
     // Then later, you can set a texture if you use the texture manager:
     img.set_preload(texture_manager.get_preload("assets/image1.png").unwrap());
+    
+    // Or with a TextureHandle returned by texture_manager.preload():
+    img.set_handle(&texture_manager, handle);
+    
+    // Images set with a handle can follow reloads of that texture (see TextureManager hot reload)
+    img.refresh(&texture_manager);
 
 3. With custom stretch and zoom options:
     let img_custom = StillImage::new(
@@ -64,26 +71,249 @@ Usage examples:
 Additional functionality:
 - Zoom controls: set_zoom(), zoom_in(), zoom_out(), reset_zoom()
 - Stretch controls: enable_stretch(), disable_stretch(), toggle_stretch()
+- Fit modes and alignment inside the width x height box: set_fit_mode(), set_alignment()
+    img.set_fit_mode(FitMode::Contain);            // Whole photo, letterboxed, no distortion
+    img.set_fit_mode(FitMode::Cover);              // Fill the box, cropping the edges
+    img.set_alignment(HAlign::Left, VAlign::Top);  // Where the photo sits (default is centered)
+  Fill is the same as stretch enabled and None the same as stretch disabled
 - Position control: set_position()
 - Check if empty: is_empty()
+- Handle missing or broken files without crashing: try_new(), try_set_texture()
+    match img.try_set_texture("assets/typo.png").await {
+        Ok(()) => {}
+        Err(err) => println!("Could not load image: {}", err), // The old texture is kept
+    }
+- Show a magenta checkerboard instead of crashing when set_texture() fails:
+    img.enable_fallback();
+    img.set_texture("assets/typo.png").await;
+    if img.is_missing() {
+        println!("{}", img.get_load_error().unwrap());
+    }
+- Build from an Image already in memory (no file access): from_image()
+    let mut image = Image::gen_image_color(64, 64, RED);
+    image.set_pixel(0, 0, BLANK);
+    let img = StillImage::from_image(image, 64.0, 64.0, 10.0, 10.0, false, 1.0);
+- Tighter or looser hitboxes: pass MaskSettings to new_with_mask() or set_texture_with_mask()
+    let img = StillImage::new_with_mask(
+        "assets/image_name.png", 100.0, 200.0, 200.0, 60.0, true, 1.0,
+        MaskSettings::threshold(128), // Ignore soft edges with alpha of 128 or less
+    ).await;
+- Draw only part of the texture: set_source_rect(), clear_source_rect()
+    img.set_source_rect(Rect::new(0.0, 0.0, 32.0, 32.0)); // Top-left 32 x 32 pixels
+- Sprite sheets: one texture backing many images (see sprite_sheet.rs)
+    let sheet = SpriteSheet::from_grid(texture_manager.get_preload("assets/hero.png").unwrap(), 32.0, 32.0);
+    img.set_sprite(&sheet, 2);
+  Stretch, zoom and hit-testing then work on the frame size instead of the whole texture
+- Animated texture changes for slideshows: transition_to(), is_transitioning()
+    img.transition_to(texture_manager.get_preload("assets/image2.png").unwrap(), Transition::Crossfade, 0.5);
+    img.transition_to(next, Transition::Slide(Direction::Left), 0.4);
+    img.transition_to(next, Transition::Wipe(Direction::Down), 0.4);
+    img.transition_to(next, Transition::ZoomFade, 0.6);
+    img.update_transition(); // Optional, once per frame: frees the old texture when done
+- Image processing on the loaded pixels: apply_filters() (see image_filters.rs)
+    img.apply_filters(&[Filter::Grayscale, Filter::Contrast(1.3)]);
+- Tint, opacity and blend modes: set_tint(), set_opacity(), set_blend_mode()
+    img.set_tint(if hovered { YELLOW } else { WHITE }); // Hover highlight
+    img.set_opacity(1.0 - fade_timer);                  // Fade out
+    img.set_blend_mode(BlendMode::Additive);            // Glow (Alpha is normal, Multiply darkens)
+- Photo viewer style zoom and pan: zoom_at(), set_pan(), pan_by(), enable_clip()
+    img.enable_clip(); // Draw only inside the width x height box, which no longer grows with zoom
+    let (_, wheel) = mouse_wheel();
+    if wheel != 0.0 {
+        img.zoom_at(mouse_position().into(), img.get_zoom_level() * if wheel > 0.0 { 1.1 } else { 1.0 / 1.1 });
+    }
+    let mouse: Vec2 = mouse_position().into();
+    if is_mouse_button_down(MouseButton::Left) {
+        img.pan_by(mouse - last_mouse); // Drag to pan
+    }
+    last_mouse = mouse;
+  With clipping on, panning is clamped so the image can't be dragged out of its box (disable_pan_clamp() turns that off)
+  Without clipping the box grows with the zoom, so panning only keeps a quarter of the image
+  over the original width x height box, and zoom_at works the same
+- Rotation, flipping and pivot point: set_rotation(), set_rotation_degrees(), rotate(),
+  flip_horizontal(), flip_vertical(), set_flip(), set_pivot()
+    img.set_pivot(Pivot::Center);    // (x, y) is now the middle of the image
+    img.set_rotation_degrees(45.0);  // Spins around the middle, clockwise
+    img.flip_horizontal();           // Face the other way
+  Zoom also grows the image around the pivot. Hit-testing and collisions follow all of these
+- Pixel-perfect hit-testing: contains_point(mouse_position().into())
+  Returns true only when the point lands on an opaque pixel of the image
+- Pixel collision between two images: collides_with(&other)
+  Use collision_with(&other) to also get the overlapping rectangle and contact point count:
+    if let Some(hit) = player.collision_with(&wall) {
+        println!("Touching {} pixels inside {:?}", hit.contact_points, hit.overlap);
+    }
 */
 use macroquad::prelude::*;
 use macroquad::texture::Texture2D;
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation, PipelineParams};
+use std::rc::Rc;
+use crate::modules::image_filters::{self, read_pixels, Filter};
+use crate::modules::image_preload::{PreloadedTexture, TextureHandle, TextureManager};
+use crate::modules::sprite_sheet::SpriteSheet;
+use crate::modules::transparency_mask::{MaskSettings, TransparencyMask};
+
+// Result of a pixel collision between two images
+// overlap is the screen-space intersection of both drawn areas and
+// contact_points is how many screen pixels inside it are opaque in both masks
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collision {
+    pub overlap: Rect,
+    pub contact_points: usize,
+}
+
+// How the image is sized inside its width x height box
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FitMode {
+    Fill,      // Stretch to exactly width x height, ignoring the aspect ratio (stretch enabled)
+    #[default]
+    None,      // Natural texture size, ignoring the box (stretch disabled)
+    Contain,   // As large as fits inside the box, keeping the aspect ratio (letterbox)
+    Cover,     // Fill the box keeping the aspect ratio, cropping whatever sticks out
+    ScaleDown, // Natural size, or Contain if the image is bigger than the box
+}
+
+impl FitMode {
+    // The mode matching the old stretch flag
+    fn from_stretch(stretch_enabled: bool) -> Self {
+        if stretch_enabled {
+            FitMode::Fill
+        } else {
+            FitMode::None
+        }
+    }
+}
+
+// Horizontal position of the image inside its box
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HAlign {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+// Vertical position of the image inside its box
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VAlign {
+    Top,
+    #[default]
+    Center,
+    Bottom,
+}
+
+// How the image's pixels combine with what is already drawn underneath
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    #[default]
+    Alpha,    // Normal drawing, see-through where the image is transparent
+    Additive, // Adds light, good for glows, sparks and damage flashes
+    Multiply, // Darkens, good for shadows and colour filters
+}
+
+// Animated change from one texture to the next, see StillImage::transition_to
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Crossfade,            // Fade the old image out while the new one fades in
+    Slide(Direction),     // The new image pushes the old one out of the box, moving this way
+    Wipe(Direction),      // The new image is uncovered by an edge moving this way
+    ZoomFade,             // The old image grows and fades while the new one settles in
+}
+
+// Which way a Slide or Wipe transition moves
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    // One step in this direction in screen space (y points down)
+    fn vector(self) -> Vec2 {
+        match self {
+            Direction::Left => vec2(-1.0, 0.0),
+            Direction::Right => vec2(1.0, 0.0),
+            Direction::Up => vec2(0.0, -1.0),
+            Direction::Down => vec2(0.0, 1.0),
+        }
+    }
+}
 
+// A running transition: how the image looked before and when the change started
+#[derive(Clone)]
+struct ActiveTransition {
+    outgoing: StillImage,
+    transition: Transition,
+    start: f64,    // get_time() when the transition began
+    duration: f64, // Seconds
+}
+
+impl ActiveTransition {
+    // How far along the transition is, from 0.0 to 1.0
+    fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        ((get_time() - self.start) / self.duration).clamp(0.0, 1.0) as f32
+    }
+}
+
+// Which point of the image's box sits at its (x, y) position
+// Rotation and zoom happen around this point, so a centered image grows and spins in place
+// With FitMode::None the box is the image itself
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Pivot {
+    #[default]
+    TopLeft,
+    Center,
+    Custom(Vec2), // Fraction of the box size: (0, 0) is the top-left corner, (1, 1) the bottom-right
+}
+
+#[derive(Clone)]
 pub struct StillImage {
     texture: Texture2D,
     x: f32,
     y: f32,
     width: f32,
     height: f32,
-    transparency_mask: Vec<u8>, // Now storing raw transparency data (bitmask)
-    stretch_enabled: bool, // Flag to control image stretching
+    transparency_mask: Rc<TransparencyMask>, // Per-pixel opacity used for hit-testing, shared with the TextureManager
+    fit_mode: FitMode, // How the image is sized inside its width x height box
+    h_align: HAlign, // Where a smaller or cropped image sits inside the box
+    v_align: VAlign,
     zoom_level: f32, // Zoom factor to scale the image
-    filename: String, // Store the original filename/path
+    filename: Rc<str>, // Store the original filename/path
+    fallback_enabled: bool, // Show a placeholder instead of panicking when set_texture fails
+    load_error: Option<ImageError>, // Most recent load failure, if any
+    handle: Option<TextureHandle>, // Set when the texture came from set_handle
+    revision: u32, // Revision of the handle's texture when it was last picked up
+    atlas_rect: Option<Rect>, // Part of the texture holding the image (atlas entries), None for the whole texture
+    source_rect: Option<Rect>, // Part of the image to draw, in image pixels, None for the whole image
+    rotation: f32, // Clockwise, in radians, around the pivot
+    flip_x: bool, // Mirror left to right
+    flip_y: bool, // Mirror top to bottom
+    pivot: Pivot, // Point of the image placed at (x, y)
+    pan: Vec2, // Offset of the drawn image from its aligned spot, in screen pixels
+    clip_enabled: bool, // Only draw inside the width x height box, which then stays the same size when zooming
+    pan_clamp_enabled: bool, // Keep panning from moving the image out of its box
+    tint: Color, // Multiplied with every pixel, WHITE leaves the image unchanged
+    opacity: f32, // 0.0 is invisible, 1.0 is fully opaque
+    blend_mode: BlendMode, // How drawn pixels combine with what is already on screen
+    transition: Option<Box<ActiveTransition>>, // The previous look, kept while a transition runs
 }
 
 impl StillImage {
     // Constructor for StillImage with asset path and x, y location
+    // Panics if the image cannot be loaded, use try_new to handle the error instead
     pub async fn new(
         asset_path: &str, 
         width: f32, 
@@ -93,64 +323,304 @@ impl StillImage {
         stretch_enabled: bool,
         zoom_level: f32
     ) -> Self {
+        Self::new_with_mask(asset_path, width, height, x, y, stretch_enabled, zoom_level, MaskSettings::default()).await
+    }
+
+    // Constructor that also chooses how the transparency mask is built (alpha threshold and mode)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new_with_mask(
+        asset_path: &str, 
+        width: f32, 
+        height: f32, 
+        x: f32, 
+        y: f32,
+        stretch_enabled: bool,
+        zoom_level: f32,
+        mask_settings: MaskSettings
+    ) -> Self {
+        Self::try_new_with_mask(asset_path, width, height, x, y, stretch_enabled, zoom_level, mask_settings)
+            .await
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    // Constructor that returns an error instead of panicking when the image cannot be loaded
+    #[allow(unused)]
+    pub async fn try_new(
+        asset_path: &str, 
+        width: f32, 
+        height: f32, 
+        x: f32, 
+        y: f32,
+        stretch_enabled: bool,
+        zoom_level: f32
+    ) -> Result<Self, ImageError> {
+        Self::try_new_with_mask(asset_path, width, height, x, y, stretch_enabled, zoom_level, MaskSettings::default()).await
+    }
+
+    // Fallible constructor that also chooses how the transparency mask is built
+    #[allow(clippy::too_many_arguments)]
+    pub async fn try_new_with_mask(
+        asset_path: &str, 
+        width: f32, 
+        height: f32, 
+        x: f32, 
+        y: f32,
+        stretch_enabled: bool,
+        zoom_level: f32,
+        mask_settings: MaskSettings
+    ) -> Result<Self, ImageError> {
         // Check if the asset path is empty
         if asset_path.is_empty() {
             // Create an empty/clear image
             let empty_texture = Texture2D::from_rgba8(1, 1, &[0, 0, 0, 0]);
-            let empty_mask = vec![0]; // Single transparent pixel
+            let empty_mask = TransparencyMask::new(1, 1); // Single transparent pixel
             
-            return Self { 
-                x, 
-                y, 
-                width, 
-                height, 
-                texture: empty_texture, 
-                transparency_mask: empty_mask,
-                stretch_enabled,
-                zoom_level: zoom_level.max(0.1), // Ensure minimum zoom
-                filename: "__empty__".to_string(), // Use a special filename
-            };
+            return Ok(Self::from_parts(empty_texture, empty_mask, "__empty__", Rect::new(x, y, width, height), stretch_enabled, zoom_level));
         }
         
         // Normal path for valid asset paths
-        let (texture, transparency_mask) = set_texture_main(asset_path).await;
-        Self { 
-            x, 
-            y, 
-            width, 
-            height, 
-            texture, 
-            transparency_mask,
-            stretch_enabled,
+        let (texture, transparency_mask) = try_set_texture_main(asset_path, mask_settings).await?;
+        Ok(Self::from_parts(texture, transparency_mask, asset_path, Rect::new(x, y, width, height), stretch_enabled, zoom_level))
+    }
+
+    // Constructor for an image built from an already decoded or procedurally created Image
+    // No file access is needed, the texture and mask both come from the given pixels
+    #[allow(unused)]
+    pub fn from_image(
+        image: Image, 
+        width: f32, 
+        height: f32, 
+        x: f32, 
+        y: f32,
+        stretch_enabled: bool,
+        zoom_level: f32
+    ) -> Self {
+        let (texture, transparency_mask) = texture_from_image(&image, MaskSettings::default());
+        Self::from_parts(texture, transparency_mask, "__image__", Rect::new(x, y, width, height), stretch_enabled, zoom_level)
+    }
+
+    // Shared by every constructor: the given texture, mask, filename and box, with default settings for the rest
+    fn from_parts(texture: Texture2D, transparency_mask: TransparencyMask, filename: &str, area: Rect, stretch_enabled: bool, zoom_level: f32) -> Self {
+        Self {
+            x: area.x,
+            y: area.y,
+            width: area.w,
+            height: area.h,
+            texture,
+            transparency_mask: Rc::new(transparency_mask),
+            fit_mode: FitMode::from_stretch(stretch_enabled),
+            h_align: HAlign::default(),
+            v_align: VAlign::default(),
             zoom_level: zoom_level.max(0.1), // Ensure minimum zoom
-            filename: asset_path.to_string(), // Store the original filename
+            filename: filename.into(),
+            fallback_enabled: false,
+            load_error: None,
+            handle: None,
+            revision: 0,
+            atlas_rect: None,
+            source_rect: None,
+            rotation: 0.0,
+            flip_x: false,
+            flip_y: false,
+            pivot: Pivot::default(),
+            pan: Vec2::ZERO,
+            clip_enabled: false,
+            pan_clamp_enabled: true,
+            tint: WHITE,
+            opacity: 1.0,
+            blend_mode: BlendMode::default(),
+            transition: None,
+        }
+    }
+
+    // Size the image is drawn at on screen, after the fit mode and zoom are applied
+    fn draw_size(&self) -> Vec2 {
+        fit_size(self.fit_mode, self.source_size(), self.size()) * self.zoom_level
+    }
+
+    // Size of the box the image is laid out in
+    // With clipping on this is always width x height, otherwise it grows with zoom
+    // and FitMode::None ignores the box, so there the box is just the image
+    fn box_size(&self) -> Vec2 {
+        if self.clip_enabled {
+            return self.size();
+        }
+        match self.fit_mode {
+            FitMode::None => self.draw_size(),
+            _ => self.size() * self.zoom_level,
+        }
+    }
+
+    // The box in screen space, before rotation
+    fn box_rect(&self) -> Rect {
+        let top_left = self.pos() - self.pivot_offset();
+        let size = self.box_size();
+        Rect::new(top_left.x, top_left.y, size.x, size.y)
+    }
+
+    // Offset from the top-left corner of the box to the pivot, in screen pixels
+    fn pivot_offset(&self) -> Vec2 {
+        self.box_size() * self.pivot_fraction()
+    }
+
+    // Pivot as a fraction of the box: 0 is left/top, 1 is right/bottom
+    fn pivot_fraction(&self) -> Vec2 {
+        match self.pivot {
+            Pivot::TopLeft => Vec2::ZERO,
+            Pivot::Center => Vec2::splat(0.5),
+            Pivot::Custom(fraction) => fraction,
+        }
+    }
+
+    // The width x height box at zoom 1, in screen space before rotation
+    // Without clipping panning keeps part of the image over this box
+    fn home_rect(&self) -> Rect {
+        let top_left = self.pos() - self.size() * self.pivot_fraction();
+        Rect::new(top_left.x, top_left.y, self.width, self.height)
+    }
+
+    // Top-left corner of the drawn image before rotation and panning
+    fn aligned_top_left(&self) -> Vec2 {
+        let box_top_left = self.pos() - self.pivot_offset();
+        box_top_left + (self.box_size() - self.draw_size()) * self.align_fraction()
+    }
+
+    // Top-left corner of the drawn image before rotation
+    fn top_left(&self) -> Vec2 {
+        self.aligned_top_left() + self.clamped_pan(self.pan)
+    }
+
+    // The pan offset limited so the image can't be dragged out of view, when clamping is on
+    // With clipping the image is kept over its whole box. Without clipping the box grows with
+    // the zoom, so clamping to it would undo every pan; instead part of the image is kept over
+    // the width x height box it started in
+    fn clamped_pan(&self, pan: Vec2) -> Vec2 {
+        if !self.pan_clamp_enabled {
+            return pan;
         }
+        let aligned = self.aligned_top_left();
+        let top_left = if self.clip_enabled {
+            clamp_to_view(aligned + pan, self.draw_size(), self.box_rect())
+        } else {
+            keep_overlapping(aligned + pan, self.draw_size(), self.home_rect(), MIN_PAN_OVERLAP)
+        };
+        top_left - aligned
+    }
+
+    // Alignment as a fraction of the free space: 0 is left/top, 1 is right/bottom
+    fn align_fraction(&self) -> Vec2 {
+        let x = match self.h_align {
+            HAlign::Left => 0.0,
+            HAlign::Center => 0.5,
+            HAlign::Right => 1.0,
+        };
+        let y = match self.v_align {
+            VAlign::Top => 0.0,
+            VAlign::Center => 0.5,
+            VAlign::Bottom => 1.0,
+        };
+        vec2(x, y)
     }
 
     // Method to draw the image with current settings
     pub fn draw(&self) {
-        // Get the size to use for drawing
-        let (draw_width, draw_height) = if self.stretch_enabled {
-            (self.width, self.height)
-        } else {
-            // Use original texture size when stretch is disabled
-            (self.texture.width(), self.texture.height())
+        let Some(active) = self.transition.as_deref().filter(|active| active.progress() < 1.0) else {
+            self.draw_layer(Vec2::ZERO, 1.0, 1.0, None);
+            return;
         };
-        
-        // Apply zoom factor
-        let final_width = draw_width * self.zoom_level;
-        let final_height = draw_height * self.zoom_level;
-        
+        // Ease in and out so the motion starts and stops gently
+        let t = active.progress();
+        let t = t * t * (3.0 - 2.0 * t);
+        let outgoing = &active.outgoing;
+        let area = rotated_bounds(self.box_rect(), self.pos(), self.rotation);
+        match active.transition {
+            Transition::Crossfade => {
+                outgoing.draw_layer(Vec2::ZERO, 1.0, 1.0 - t, None);
+                self.draw_layer(Vec2::ZERO, 1.0, t, None);
+            }
+            Transition::Slide(direction) => {
+                // The new image pushes the old one out of the box
+                let step = direction.vector() * area.size();
+                outgoing.draw_layer(step * t, 1.0, 1.0, Some(area));
+                self.draw_layer(step * (t - 1.0), 1.0, 1.0, Some(area));
+            }
+            Transition::Wipe(direction) => {
+                outgoing.draw_layer(Vec2::ZERO, 1.0, 1.0, None);
+                self.draw_layer(Vec2::ZERO, 1.0, 1.0, Some(wipe_rect(area, direction, t)));
+            }
+            Transition::ZoomFade => {
+                outgoing.draw_layer(Vec2::ZERO, 1.0 + 0.2 * t, 1.0 - t, None);
+                self.draw_layer(Vec2::ZERO, 0.8 + 0.2 * t, t, None);
+            }
+        }
+    }
+
+    // Draw the image moved by offset, scaled around its middle and faded by opacity
+    // clip limits drawing to a screen rectangle, on top of the image's own clipping
+    fn draw_layer(&self, offset: Vec2, scale: f32, opacity: f32, clip: Option<Rect>) {
+        let size = self.draw_size() * scale;
+        let top_left = self.top_left() + (self.draw_size() - size) * 0.5 + offset;
+        let mut dest = Rect::new(top_left.x, top_left.y, size.x, size.y);
+        let mut source = self.texture_source();
+        if self.clip_enabled {
+            // Crop to the box before rotating, so a rotated image is clipped to its rotated box
+            // The box moves and scales with the image during transitions
+            let center = self.top_left() + self.draw_size() * 0.5;
+            let own = self.box_rect();
+            let own = Rect::new(0.0, 0.0, own.w * scale, own.h * scale).offset(center + (own.point() - center) * scale + offset);
+            let full = source.unwrap_or_else(|| Rect::new(0.0, 0.0, self.texture.width(), self.texture.height()));
+            match clip_draw(dest, full, own, self.flip_x, self.flip_y) {
+                Some((cropped_dest, cropped_source)) => {
+                    dest = cropped_dest;
+                    source = Some(cropped_source);
+                }
+                None => return, // Nothing left to draw
+            }
+        }
+        let material = blend_material(self.blend_mode);
+        if let Some(material) = &material {
+            gl_use_material(material);
+        }
+        if let Some(clip) = clip {
+            // Transition clips are upright screen rectangles, so a scissor is enough
+            // Scissor rectangles are in physical pixels, so scale by the window's DPI.
+            // This assumes the default screen camera
+            let dpi = screen_dpi_scale();
+            let gl = unsafe { get_internal_gl() }.quad_gl;
+            gl.scissor(Some((
+                (clip.x * dpi) as i32,
+                (clip.y * dpi) as i32,
+                (clip.w * dpi).ceil() as i32,
+                (clip.h * dpi).ceil() as i32,
+            )));
+        }
+        let mut color = self.draw_color();
+        color.a *= opacity;
         draw_texture_ex(
             &self.texture,
-            self.x,
-            self.y,
-            WHITE,
+            dest.x,
+            dest.y,
+            color,
             DrawTextureParams {
-                dest_size: Some(vec2(final_width, final_height)),
-                ..Default::default()
+                dest_size: Some(dest.size()),
+                source,
+                rotation: self.rotation,
+                flip_x: self.flip_x,
+                flip_y: self.flip_y,
+                pivot: Some(self.pos() + offset),
             },
         );
+        if clip.is_some() {
+            unsafe { get_internal_gl() }.quad_gl.scissor(None);
+        }
+        if material.is_some() {
+            gl_use_default_material();
+        }
+    }
+
+    // Colour passed to the GPU: the tint with the opacity folded into its alpha
+    fn draw_color(&self) -> Color {
+        Color::new(self.tint.r, self.tint.g, self.tint.b, self.tint.a * self.opacity)
     }
 
     // Accessors for image properties
@@ -162,9 +632,37 @@ impl StillImage {
     pub fn size(&self) -> Vec2 {
         vec2(self.width, self.height)
     }
+    // Size of the part being drawn in texture pixels
+    // This is the source rectangle when one is set, minus anything FitMode::Cover crops off
     #[allow(unused)]
     pub fn texture_size(&self) -> Vec2 {
-        vec2(self.texture.width(), self.texture.height())
+        self.visible_source().size()
+    }
+    // Size of the source rectangle, or the whole image when there is none
+    fn source_size(&self) -> Vec2 {
+        match self.source_rect {
+            Some(source) => source.size(),
+            None => self.image_size(),
+        }
+    }
+    // The part of the image actually drawn, in image pixels
+    fn visible_source(&self) -> Rect {
+        let source = self.source_rect.unwrap_or_else(|| {
+            let size = self.image_size();
+            Rect::new(0.0, 0.0, size.x, size.y)
+        });
+        match self.fit_mode {
+            FitMode::Cover => cover_crop(source, self.size(), self.align_fraction()),
+            _ => source,
+        }
+    }
+    // Size of the whole image in pixels, ignoring any source rectangle
+    #[allow(unused)]
+    pub fn image_size(&self) -> Vec2 {
+        match self.atlas_rect {
+            Some(atlas) => atlas.size(),
+            None => vec2(self.texture.width(), self.texture.height()),
+        }
     }
     #[allow(unused)]
     pub fn set_position(&mut self, pos: Vec2) {
@@ -172,49 +670,250 @@ impl StillImage {
         self.y = pos[1];
     }
 
+    // Draw only part of the image, for example one frame of a sprite sheet
+    // The rectangle is in image pixels, snapped to whole pixels and clipped to the image
+    // Returns false and leaves the image unchanged if the rectangle is entirely outside it
+    #[allow(unused)]
+    pub fn set_source_rect(&mut self, rect: Rect) -> bool {
+        let size = self.image_size();
+        let snapped = Rect::new(rect.x.floor(), rect.y.floor(), rect.w.round(), rect.h.round());
+        match snapped.intersect(Rect::new(0.0, 0.0, size.x, size.y)) {
+            Some(clipped) if clipped.w >= 1.0 && clipped.h >= 1.0 => {
+                self.source_rect = Some(clipped);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Go back to drawing the whole image
+    #[allow(unused)]
+    pub fn clear_source_rect(&mut self) {
+        self.source_rect = None;
+    }
+
+    #[allow(unused)]
+    pub fn get_source_rect(&self) -> Option<Rect> {
+        self.source_rect
+    }
+
+    // Show one frame of a sprite sheet, switching to the sheet's texture
+    // Returns false and leaves the image unchanged if the frame index is out of range
+    #[allow(unused)]
+    pub fn set_sprite(&mut self, sheet: &SpriteSheet, frame: usize) -> bool {
+        match sheet.frame(frame) {
+            Some(rect) => {
+                self.set_preload(sheet.preloaded());
+                self.set_source_rect(rect)
+            }
+            None => false,
+        }
+    }
+
+    // Rectangle of the texture to draw: the visible source moved into the atlas page if needed
+    fn texture_source(&self) -> Option<Rect> {
+        let visible = self.visible_source();
+        match self.atlas_rect {
+            Some(atlas) => Some(visible.offset(atlas.point())),
+            None => Some(visible),
+        }
+    }
+
     // Get the original filename/path of the loaded image
     #[allow(unused)]
     pub fn get_filename(&self) -> &str {
         &self.filename
     }
 
-    // Get the transparency mask (bitmask)
+    // Get the transparency mask
     #[allow(unused)]
-    pub fn get_mask(&self) -> Vec<u8> {
-        return self.transparency_mask.clone();
+    pub fn get_mask(&self) -> &TransparencyMask {
+        &self.transparency_mask
+    }
+
+    // Check if a screen point lands on an opaque pixel of the image
+    // The point is mapped back through rotation, position, stretch, zoom and flips into texture space
+    #[allow(unused)]
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        self.is_collidable() && mask_hit(&self.transparency_mask, &self.mask_placement(), point)
+    }
+
+    // Where the mask currently lands on screen, for hit-testing many points in a row
+    fn mask_placement(&self) -> MaskPlacement {
+        MaskPlacement {
+            pos: self.pos(),
+            rotation: self.rotation,
+            clip: self.clip_enabled.then(|| self.box_rect()),
+            top_left: self.top_left(),
+            draw_size: self.draw_size(),
+            visible: self.visible_source(),
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+        }
+    }
+
+    // Screen-space rectangle the image currently covers
+    // For a rotated image this is the smallest upright rectangle around it
+    // With clipping on it is limited to the box
+    #[allow(unused)]
+    pub fn bounds(&self) -> Rect {
+        let top_left = self.top_left();
+        let size = self.draw_size();
+        let drawn = rotated_bounds(Rect::new(top_left.x, top_left.y, size.x, size.y), self.pos(), self.rotation);
+        if !self.clip_enabled {
+            return drawn;
+        }
+        let clip = rotated_bounds(self.box_rect(), self.pos(), self.rotation);
+        drawn.intersect(clip).unwrap_or(Rect::new(clip.x, clip.y, 0.0, 0.0))
+    }
+
+    // Check if the opaque pixels of two images overlap on screen
+    #[allow(unused)]
+    pub fn collides_with(&self, other: &StillImage) -> bool {
+        self.scan_collision(other, true).is_some()
+    }
+
+    // Like collides_with, but also reports the overlapping rectangle and how many pixels touch
+    #[allow(unused)]
+    pub fn collision_with(&self, other: &StillImage) -> Option<Collision> {
+        self.scan_collision(other, false)
+    }
+
+    // Shared collision scan: AABB rejection first, then compare both masks
+    // one screen pixel at a time inside the overlapping region
+    fn scan_collision(&self, other: &StillImage, stop_at_first: bool) -> Option<Collision> {
+        if !self.is_collidable() || !other.is_collidable() {
+            return None;
+        }
+        let overlap = self.bounds().intersect(other.bounds())?;
+        let contact_points = mask_overlap(
+            (&self.transparency_mask, &self.mask_placement()),
+            (&other.transparency_mask, &other.mask_placement()),
+            overlap,
+            stop_at_first,
+        );
+        if contact_points > 0 {
+            Some(Collision { overlap, contact_points })
+        } else {
+            None
+        }
     }
     #[allow(unused)]
     pub async fn set_texture(&mut self, texture_path: &str) {
-        let (texture, transparency_mask) = set_texture_main(texture_path).await;
+        self.set_texture_with_mask(texture_path, MaskSettings::default()).await;
+    }
+
+    // Load a new texture, choosing how its transparency mask is built
+    // With fallback enabled a failed load shows the missing texture placeholder instead of panicking
+    #[allow(unused)]
+    pub async fn set_texture_with_mask(&mut self, texture_path: &str, mask_settings: MaskSettings) {
+        if let Err(err) = self.try_set_texture_with_mask(texture_path, mask_settings).await {
+            if !self.fallback_enabled {
+                panic!("{err}");
+            }
+            self.set_preload(missing_placeholder(texture_path));
+            self.load_error = Some(err);
+        }
+    }
+
+    // Load a new texture, returning an error instead of panicking
+    // The current texture is kept if loading fails
+    #[allow(unused)]
+    pub async fn try_set_texture(&mut self, texture_path: &str) -> Result<(), ImageError> {
+        self.try_set_texture_with_mask(texture_path, MaskSettings::default()).await
+    }
+
+    // Fallible texture load that also chooses how the transparency mask is built
+    #[allow(unused)]
+    pub async fn try_set_texture_with_mask(&mut self, texture_path: &str, mask_settings: MaskSettings) -> Result<(), ImageError> {
+        let (texture, transparency_mask) = try_set_texture_main(texture_path, mask_settings).await?;
         self.texture = texture;
-        self.transparency_mask = transparency_mask;
-        self.filename = texture_path.to_string(); // Update the filename when texture changes
+        self.transparency_mask = Rc::new(transparency_mask);
+        self.filename = texture_path.into(); // Update the filename when texture changes
+        self.load_error = None;
+        self.handle = None;
+        self.atlas_rect = None;
+        self.source_rect = None;
+        Ok(())
+    }
+
+    // Methods to toggle the missing texture fallback used by set_texture
+    #[allow(unused)]
+    pub fn enable_fallback(&mut self) {
+        self.fallback_enabled = true;
+    }
+
+    #[allow(unused)]
+    pub fn disable_fallback(&mut self) {
+        self.fallback_enabled = false;
+    }
+
+    #[allow(unused)]
+    pub fn is_fallback_enabled(&self) -> bool {
+        self.fallback_enabled
+    }
+
+    // The error from the last set_texture call that fell back to the placeholder
+    #[allow(unused)]
+    pub fn get_load_error(&self) -> Option<&ImageError> {
+        self.load_error.as_ref()
+    }
+
+    // Check if the image is currently showing the missing texture placeholder
+    #[allow(unused)]
+    pub fn is_missing(&self) -> bool {
+        self.filename.starts_with(MISSING_PREFIX)
     }
     
     // Methods to toggle stretching
+    // Stretching is FitMode::Fill and not stretching is FitMode::None
     #[allow(unused)]
     pub fn enable_stretch(&mut self) {
-        self.stretch_enabled = true;
+        self.fit_mode = FitMode::Fill;
     }
     
     #[allow(unused)]
     pub fn disable_stretch(&mut self) {
-        self.stretch_enabled = false;
+        self.fit_mode = FitMode::None;
     }
     
     #[allow(unused)]
     pub fn toggle_stretch(&mut self) {
-        self.stretch_enabled = !self.stretch_enabled;
+        self.fit_mode = FitMode::from_stretch(self.fit_mode != FitMode::Fill);
     }
     
     #[allow(unused)]
     pub fn is_stretch_enabled(&self) -> bool {
-        self.stretch_enabled
+        self.fit_mode == FitMode::Fill
     }
     
     #[allow(unused)]
     pub fn set_stretch(&mut self, enabled: bool) {
-        self.stretch_enabled = enabled;
+        self.fit_mode = FitMode::from_stretch(enabled);
+    }
+    
+    // Fit mode methods
+    #[allow(unused)]
+    pub fn set_fit_mode(&mut self, fit_mode: FitMode) {
+        self.fit_mode = fit_mode;
+    }
+    
+    #[allow(unused)]
+    pub fn get_fit_mode(&self) -> FitMode {
+        self.fit_mode
+    }
+    
+    // Where the image sits inside its box when it is smaller than the box (Contain, ScaleDown)
+    // and which part is kept when it is cropped (Cover)
+    #[allow(unused)]
+    pub fn set_alignment(&mut self, h_align: HAlign, v_align: VAlign) {
+        self.h_align = h_align;
+        self.v_align = v_align;
+    }
+    
+    #[allow(unused)]
+    pub fn get_alignment(&self) -> (HAlign, VAlign) {
+        (self.h_align, self.v_align)
     }
     
     // Zoom methods
@@ -249,10 +948,185 @@ impl StillImage {
         self.zoom_level = 1.0;
     }
     
+    // Zoom so the part of the image under a screen point stays under it, like mouse-wheel zoom
+    //     img.zoom_at(mouse_position().into(), img.get_zoom_level() * 1.1);
+    // Works by adjusting the pan offset, so pan clamping still applies: with clipping on the box stays
+    // covered and zooming out near an edge moves the image back into place, and without clipping
+    // zooming far from the image can't lose it
+    #[allow(unused)]
+    pub fn zoom_at(&mut self, focal_point: Vec2, zoom_level: f32) {
+        let focal_point = rotate_around(focal_point, self.pos(), -self.rotation);
+        let old_top_left = self.top_left();
+        let old_zoom = self.zoom_level;
+        self.zoom_level = zoom_level.max(0.1);
+        let new_top_left = zoom_about(old_top_left, focal_point, old_zoom, self.zoom_level);
+        self.set_pan(new_top_left - self.aligned_top_left());
+    }
+    
+    // Pan methods, moving the drawn image inside its box (in screen pixels)
+    #[allow(unused)]
+    pub fn set_pan(&mut self, pan: Vec2) {
+        self.pan = self.clamped_pan(pan);
+    }
+    
+    #[allow(unused)]
+    pub fn pan_by(&mut self, delta: Vec2) {
+        self.set_pan(self.pan + delta);
+    }
+    
+    #[allow(unused)]
+    pub fn get_pan(&self) -> Vec2 {
+        self.clamped_pan(self.pan)
+    }
+    
+    #[allow(unused)]
+    pub fn reset_pan(&mut self) {
+        self.pan = Vec2::ZERO;
+    }
+    
+    // Clipping methods: only draw (and hit-test) inside the width x height box
+    // While clipping the box keeps its size when zooming, so zoom and pan work like a photo viewer
+    #[allow(unused)]
+    pub fn enable_clip(&mut self) {
+        self.clip_enabled = true;
+    }
+    
+    #[allow(unused)]
+    pub fn disable_clip(&mut self) {
+        self.clip_enabled = false;
+    }
+    
+    #[allow(unused)]
+    pub fn is_clip_enabled(&self) -> bool {
+        self.clip_enabled
+    }
+    
+    // Pan clamping methods. When on (the default) and clipping is on, an image bigger than its box
+    // always covers it and a smaller one always stays inside it. Without clipping at least a quarter
+    // of the image (or of the box, if smaller) stays over the width x height box
+    #[allow(unused)]
+    pub fn enable_pan_clamp(&mut self) {
+        self.pan_clamp_enabled = true;
+    }
+    
+    #[allow(unused)]
+    pub fn disable_pan_clamp(&mut self) {
+        self.pan_clamp_enabled = false;
+    }
+    
+    #[allow(unused)]
+    pub fn is_pan_clamp_enabled(&self) -> bool {
+        self.pan_clamp_enabled
+    }
+    
+    // Tint methods. The tint is multiplied with every pixel, so WHITE shows the image unchanged
+    //     img.set_tint(RED);  // Damage flash
+    #[allow(unused)]
+    pub fn set_tint(&mut self, tint: Color) {
+        self.tint = tint;
+    }
+    
+    #[allow(unused)]
+    pub fn get_tint(&self) -> Color {
+        self.tint
+    }
+    
+    #[allow(unused)]
+    pub fn clear_tint(&mut self) {
+        self.tint = WHITE;
+    }
+    
+    // Opacity methods, from 0.0 (invisible) to 1.0 (fully opaque)
+    // Opacity only changes how the image looks, hit-testing still uses the mask
+    #[allow(unused)]
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+    
+    #[allow(unused)]
+    pub fn get_opacity(&self) -> f32 {
+        self.opacity
+    }
+    
+    // Blend mode methods
+    #[allow(unused)]
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+    
+    #[allow(unused)]
+    pub fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+    
+    // Rotation methods, clockwise around the pivot
+    #[allow(unused)]
+    pub fn set_rotation(&mut self, radians: f32) {
+        self.rotation = radians;
+    }
+    
+    #[allow(unused)]
+    pub fn set_rotation_degrees(&mut self, degrees: f32) {
+        self.rotation = degrees.to_radians();
+    }
+    
+    #[allow(unused)]
+    pub fn rotate(&mut self, radians: f32) {
+        self.rotation += radians;
+    }
+    
+    #[allow(unused)]
+    pub fn get_rotation(&self) -> f32 {
+        self.rotation
+    }
+    
+    #[allow(unused)]
+    pub fn get_rotation_degrees(&self) -> f32 {
+        self.rotation.to_degrees()
+    }
+    
+    // Flip methods, mirroring the image in place
+    #[allow(unused)]
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+    }
+    
+    #[allow(unused)]
+    pub fn flip_horizontal(&mut self) {
+        self.flip_x = !self.flip_x;
+    }
+    
+    #[allow(unused)]
+    pub fn flip_vertical(&mut self) {
+        self.flip_y = !self.flip_y;
+    }
+    
+    #[allow(unused)]
+    pub fn is_flipped_x(&self) -> bool {
+        self.flip_x
+    }
+    
+    #[allow(unused)]
+    pub fn is_flipped_y(&self) -> bool {
+        self.flip_y
+    }
+    
+    // Choose which point of the image sits at (x, y); rotation and zoom happen around it
+    #[allow(unused)]
+    pub fn set_pivot(&mut self, pivot: Pivot) {
+        self.pivot = pivot;
+    }
+    
+    #[allow(unused)]
+    pub fn get_pivot(&self) -> Pivot {
+        self.pivot
+    }
+    
     // Check if the image is currently cleared/empty
     #[allow(unused)]
     pub fn is_empty(&self) -> bool {
-        self.texture.width() == 1.0 && self.texture.height() == 1.0
+        self.image_size() == vec2(1.0, 1.0)
     }
     
     // Check if collision should be performed (not empty)
@@ -262,12 +1136,111 @@ impl StillImage {
     }
     
     // Public method for setting a preloaded texture that accepts the tuple directly
+    // The mask and filename are shared with the TextureManager, so this never copies pixel data
+    // Any source rectangle is cleared
     #[allow(unused)]
-    pub fn set_preload(&mut self, preloaded: (Texture2D, Vec<u8>, String)) {
-        let (texture, mask, filename) = preloaded;
+    pub fn set_preload(&mut self, preloaded: PreloadedTexture) {
+        let (texture, mask, filename, atlas_rect) = preloaded;
         self.texture = texture;
         self.transparency_mask = mask;
         self.filename = filename;
+        self.atlas_rect = atlas_rect;
+        self.source_rect = None;
+        self.handle = None;
+    }
+
+    // Switch to a preloaded texture with an animated transition lasting duration seconds
+    // The old texture is kept until the transition finishes, and the new one is used for hit-testing
+    // straight away. Starting a new transition while one is running jumps from the current image
+    #[allow(unused)]
+    pub fn transition_to(&mut self, preloaded: PreloadedTexture, transition: Transition, duration: f32) {
+        let mut outgoing = self.clone();
+        outgoing.transition = None;
+        self.set_preload(preloaded);
+        self.transition = Some(Box::new(ActiveTransition {
+            outgoing,
+            transition,
+            start: get_time(),
+            duration: duration as f64,
+        }));
+    }
+
+    // Check if a transition started by transition_to is still running
+    #[allow(unused)]
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.as_ref().is_some_and(|active| active.progress() < 1.0)
+    }
+
+    // Release the old texture once a transition has finished
+    // Optional, but call it once per frame to free the old image as early as possible
+    // Returns true on the call where a transition finished
+    #[allow(unused)]
+    pub fn update_transition(&mut self) -> bool {
+        if self.transition.is_some() && !self.is_transitioning() {
+            self.transition = None;
+            return true;
+        }
+        false
+    }
+
+    // Jump straight to the end of a running transition
+    #[allow(unused)]
+    pub fn finish_transition(&mut self) {
+        self.transition = None;
+    }
+
+    // Run image processing filters on this image's pixels (see image_filters.rs)
+    // The texture and transparency mask are rebuilt from the result. The TextureManager's
+    // copy is not changed, so other images sharing the texture keep the original
+    // The pixels are read back from the GPU, so this needs the window to be open
+    #[allow(unused)]
+    pub fn apply_filters(&mut self, filters: &[Filter]) {
+        let image = image_filters::apply_all(&read_pixels(&self.texture, self.atlas_rect), filters);
+        let (texture, mask) = texture_from_image(&image, self.transparency_mask.settings());
+        self.texture = texture;
+        self.transparency_mask = Rc::new(mask);
+        self.atlas_rect = None; // The processed copy is a texture of its own
+        self.handle = None; // A reload of the original would undo the filters
+    }
+
+    // Set the texture from a TextureManager handle
+    // Returns false and leaves the image unchanged if the handle is stale
+    #[allow(unused)]
+    pub fn set_handle(&mut self, texture_manager: &TextureManager, handle: TextureHandle) -> bool {
+        match texture_manager.get(handle) {
+            Some(preloaded) => {
+                self.set_preload(preloaded);
+                self.handle = Some(handle);
+                self.revision = texture_manager.revision(handle).unwrap_or_default();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Pick up a reloaded texture and mask for the handle set with set_handle
+    // Call once per frame when using TextureManager::reload or hot reload
+    // Returns true if the image changed
+    #[allow(unused)]
+    pub fn refresh(&mut self, texture_manager: &TextureManager) -> bool {
+        match self.handle {
+            Some(handle) if texture_manager.revision(handle).is_some_and(|revision| revision != self.revision) => {
+                // Keep showing the same part of the image, e.g. the current sprite frame
+                let source_rect = self.source_rect;
+                let changed = self.set_handle(texture_manager, handle);
+                if let Some(rect) = source_rect {
+                    self.set_source_rect(rect);
+                }
+                changed
+            }
+            _ => false,
+        }
+    }
+
+    // The handle the current texture came from, if it was set with set_handle
+    #[allow(unused)]
+    pub fn get_handle(&self) -> Option<TextureHandle> {
+        self.handle
     }
 
     /// Clears the image by setting it to a 1x1 transparent pixel
@@ -275,46 +1248,852 @@ impl StillImage {
     pub fn clear(&mut self) {
         // Create a 1x1 transparent pixel texture
         let empty_texture = Texture2D::from_rgba8(1, 1, &[0, 0, 0, 0]);
-        let empty_mask = vec![0]; // Single transparent pixel
+        let empty_mask = TransparencyMask::new(1, 1); // Single transparent pixel
         
         // Update the image object with this empty texture
         self.texture = empty_texture;
-        self.transparency_mask = empty_mask;
-        self.filename = "__empty__".to_string();
+        self.transparency_mask = Rc::new(empty_mask);
+        self.filename = "__empty__".into();
+        self.handle = None;
+        self.atlas_rect = None;
+        self.source_rect = None;
+    }
+}
+
+// Size an image of tex_size is drawn at inside a box of box_size, before zoom
+fn fit_size(fit_mode: FitMode, tex_size: Vec2, box_size: Vec2) -> Vec2 {
+    if tex_size.x <= 0.0 || tex_size.y <= 0.0 {
+        return tex_size;
+    }
+    let contain = tex_size * (box_size.x / tex_size.x).min(box_size.y / tex_size.y);
+    match fit_mode {
+        FitMode::Fill | FitMode::Cover => box_size, // Cover crops the source to the box's shape instead
+        FitMode::None => tex_size,
+        FitMode::Contain => contain,
+        FitMode::ScaleDown if tex_size.x <= box_size.x && tex_size.y <= box_size.y => tex_size,
+        FitMode::ScaleDown => contain,
+    }
+}
+
+// Part of source that fills a box of box_size without distortion, positioned by align (0 to 1 on each axis)
+fn cover_crop(source: Rect, box_size: Vec2, align: Vec2) -> Rect {
+    if box_size.x <= 0.0 || box_size.y <= 0.0 || source.w <= 0.0 || source.h <= 0.0 {
+        return source;
+    }
+    let scale = (box_size.x / source.w).max(box_size.y / source.h);
+    let visible = box_size / scale;
+    let offset = (source.size() - visible) * align;
+    Rect::new(source.x + offset.x, source.y + offset.y, visible.x, visible.y)
+}
+
+// New top-left corner for an image drawn at top_left when its zoom changes from old_zoom to
+// new_zoom, chosen so the image point under focal_point stays there
+pub fn zoom_about(top_left: Vec2, focal_point: Vec2, old_zoom: f32, new_zoom: f32) -> Vec2 {
+    if old_zoom <= 0.0 {
+        return top_left;
+    }
+    focal_point - (focal_point - top_left) * (new_zoom / old_zoom)
+}
+
+// Share of the image (or of the box, if that is smaller) that panning without clipping keeps over the box
+const MIN_PAN_OVERLAP: f32 = 0.25;
+
+// Limit where an image of size can be placed so it still overlaps view, returning its clamped top-left
+// On each axis at least min_overlap of the shorter of the image and the view stays inside the view
+pub fn keep_overlapping(top_left: Vec2, size: Vec2, view: Rect, min_overlap: f32) -> Vec2 {
+    let clamp_axis = |start: f32, length: f32, view_start: f32, view_length: f32| {
+        let overlap = length.min(view_length) * min_overlap;
+        start.clamp(view_start - length + overlap, view_start + view_length - overlap)
+    };
+    vec2(
+        clamp_axis(top_left.x, size.x, view.x, view.w),
+        clamp_axis(top_left.y, size.y, view.y, view.h),
+    )
+}
+
+// Limit where an image of size can be placed relative to view, returning its clamped top-left
+// On each axis a larger image must cover the view and a smaller one must stay inside it
+pub fn clamp_to_view(top_left: Vec2, size: Vec2, view: Rect) -> Vec2 {
+    let clamp_axis = |start: f32, length: f32, view_start: f32, view_length: f32| {
+        let slack = view_length - length;
+        let (low, high) = if slack < 0.0 { (view_start + slack, view_start) } else { (view_start, view_start + slack) };
+        start.clamp(low, high)
+    };
+    vec2(
+        clamp_axis(top_left.x, size.x, view.x, view.w),
+        clamp_axis(top_left.y, size.y, view.y, view.h),
+    )
+}
+
+// Smallest upright rectangle around rect after rotating it around center
+fn rotated_bounds(rect: Rect, center: Vec2, radians: f32) -> Rect {
+    if radians == 0.0 {
+        return rect;
+    }
+    let corners = [
+        rect.point(),
+        rect.point() + vec2(rect.w, 0.0),
+        rect.point() + rect.size(),
+        rect.point() + vec2(0.0, rect.h),
+    ]
+    .map(|corner| rotate_around(corner, center, radians));
+    let min = corners.iter().fold(corners[0], |min, corner| min.min(*corner));
+    let max = corners.iter().fold(corners[0], |max, corner| max.max(*corner));
+    Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+}
+
+// Vertex shader shared by the blend mode materials, the same as macroquad's default one
+const BLEND_VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}"#;
+
+// Fragment shader for additive blending, the same as macroquad's default one
+const ADDITIVE_FRAGMENT: &str = r#"#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}"#;
+
+// Fragment shader for multiply blending
+// Transparent pixels fade towards white so they leave the screen unchanged
+const MULTIPLY_FRAGMENT: &str = r#"#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    lowp vec4 pixel = color * texture2D(Texture, uv);
+    gl_FragColor = vec4(mix(vec3(1.0), pixel.rgb, pixel.a), 1.0);
+}"#;
+
+thread_local! {
+    // Blend mode materials, built on first use and shared by every StillImage
+    static BLEND_MATERIALS: std::cell::RefCell<[Option<Material>; 2]> = const { std::cell::RefCell::new([None, None]) };
+}
+
+// Material to draw with for a blend mode, None for normal alpha blending
+fn blend_material(blend_mode: BlendMode) -> Option<Material> {
+    let (index, fragment, color_blend) = match blend_mode {
+        BlendMode::Alpha => return None,
+        BlendMode::Additive => (
+            0,
+            ADDITIVE_FRAGMENT,
+            BlendState::new(Equation::Add, BlendFactor::Value(BlendValue::SourceAlpha), BlendFactor::One),
+        ),
+        BlendMode::Multiply => (
+            1,
+            MULTIPLY_FRAGMENT,
+            BlendState::new(Equation::Add, BlendFactor::Value(BlendValue::DestinationColor), BlendFactor::Zero),
+        ),
+    };
+    BLEND_MATERIALS.with(|materials| {
+        let mut materials = materials.borrow_mut();
+        if materials[index].is_none() {
+            let material = load_material(
+                ShaderSource::Glsl {
+                    vertex: BLEND_VERTEX,
+                    fragment,
+                },
+                MaterialParams {
+                    pipeline_params: PipelineParams {
+                        color_blend: Some(color_blend),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+            .unwrap_or_else(|err| panic!("Failed to load blend shader: {err}"));
+            materials[index] = Some(material);
+        }
+        materials[index].clone()
+    })
+}
+
+// Part of area already uncovered by a wipe moving in direction, t from 0.0 to 1.0
+fn wipe_rect(area: Rect, direction: Direction, t: f32) -> Rect {
+    match direction {
+        Direction::Left => Rect::new(area.x + area.w * (1.0 - t), area.y, area.w * t, area.h),
+        Direction::Right => Rect::new(area.x, area.y, area.w * t, area.h),
+        Direction::Up => Rect::new(area.x, area.y + area.h * (1.0 - t), area.w, area.h * t),
+        Direction::Down => Rect::new(area.x, area.y, area.w, area.h * t),
+    }
+}
+
+// Crop an upright dest rectangle to area and cut the matching part out of source
+// Flipped images are mirrored, so cropping their left edge cuts the right of the source
+// Returns None when nothing of dest is inside area
+fn clip_draw(dest: Rect, source: Rect, area: Rect, flip_x: bool, flip_y: bool) -> Option<(Rect, Rect)> {
+    let crop = dest.intersect(area).filter(|crop| crop.w > 0.0 && crop.h > 0.0)?;
+    // Fractions of dest kept on each side
+    let (mut left, mut right) = ((crop.x - dest.x) / dest.w, (crop.right() - dest.x) / dest.w);
+    let (mut top, mut bottom) = ((crop.y - dest.y) / dest.h, (crop.bottom() - dest.y) / dest.h);
+    if flip_x {
+        (left, right) = (1.0 - right, 1.0 - left);
+    }
+    if flip_y {
+        (top, bottom) = (1.0 - bottom, 1.0 - top);
+    }
+    let cropped = Rect::new(
+        source.x + source.w * left,
+        source.y + source.h * top,
+        source.w * (right - left),
+        source.h * (bottom - top),
+    );
+    Some((crop, cropped))
+}
+
+// Rotate a point clockwise (on screen, where y points down) around a center
+fn rotate_around(point: Vec2, center: Vec2, radians: f32) -> Vec2 {
+    if radians == 0.0 {
+        return point;
+    }
+    let (sin, cos) = radians.sin_cos();
+    let offset = point - center;
+    center + vec2(offset.x * cos - offset.y * sin, offset.x * sin + offset.y * cos)
+}
+
+// Where an image's mask lands on screen: everything contains_point needs, worked out once
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MaskPlacement {
+    pos: Vec2,          // Rotation centre
+    rotation: f32,      // Radians, clockwise
+    clip: Option<Rect>, // The box before rotation, when clipping is on
+    top_left: Vec2,     // Drawn image before rotation
+    draw_size: Vec2,
+    visible: Rect, // Part of the mask that is drawn, in image pixels
+    flip_x: bool,
+    flip_y: bool,
+}
+
+impl MaskPlacement {
+    // Mask pixel under a screen point, or None if the point misses the drawn image
+    fn texel(&self, point: Vec2) -> Option<(usize, usize)> {
+        // Undo the rotation so the rest of the math works on an upright image
+        let point = rotate_around(point, self.pos, -self.rotation);
+        if self.clip.is_some_and(|clip| !clip.contains(point)) {
+            return None;
+        }
+        let tex_size = self.visible.size();
+        let (tx, ty) = screen_to_texel(point, self.top_left, self.draw_size, tex_size)?;
+        let tx = if self.flip_x { (tex_size.x as usize).saturating_sub(1 + tx) } else { tx };
+        let ty = if self.flip_y { (tex_size.y as usize).saturating_sub(1 + ty) } else { ty };
+        // The mask covers the whole image, so shift texels by the visible part's corner
+        Some((self.visible.x as usize + tx, self.visible.y as usize + ty))
+    }
+}
+
+// Check if a screen point lands on an opaque pixel of a placed mask
+fn mask_hit(mask: &TransparencyMask, placement: &MaskPlacement, point: Vec2) -> bool {
+    placement.texel(point).is_some_and(|(x, y)| mask.get(x, y))
+}
+
+// Count the screen pixels inside area where both placed masks are opaque,
+// sampling at pixel centres so both images agree on which texel is hit
+// With stop_at_first the count stops at 1
+fn mask_overlap(a: (&TransparencyMask, &MaskPlacement), b: (&TransparencyMask, &MaskPlacement), area: Rect, stop_at_first: bool) -> usize {
+    let mut contact_points = 0;
+    let x_start = area.x.floor() as i32;
+    let y_start = area.y.floor() as i32;
+    let x_end = (area.x + area.w).ceil() as i32;
+    let y_end = (area.y + area.h).ceil() as i32;
+    for sy in y_start..y_end {
+        for sx in x_start..x_end {
+            let point = vec2(sx as f32 + 0.5, sy as f32 + 0.5);
+            if mask_hit(a.0, a.1, point) && mask_hit(b.0, b.1, point) {
+                contact_points += 1;
+                if stop_at_first {
+                    return contact_points;
+                }
+            }
+        }
+    }
+    contact_points
+}
+
+// Map a screen point to the texel it covers when a texture of tex_size is drawn at pos with draw_size
+// Returns None when the point falls outside the drawn area
+fn screen_to_texel(point: Vec2, pos: Vec2, draw_size: Vec2, tex_size: Vec2) -> Option<(usize, usize)> {
+    if draw_size.x <= 0.0 || draw_size.y <= 0.0 {
+        return None;
+    }
+    let local = point - pos;
+    if local.x < 0.0 || local.y < 0.0 || local.x >= draw_size.x || local.y >= draw_size.y {
+        return None;
     }
+    let tx = (local.x / draw_size.x * tex_size.x) as usize;
+    let ty = (local.y / draw_size.y * tex_size.y) as usize;
+    // Guard against float rounding landing exactly on the far edge. A Cover crop can be
+    // under one texel wide, which still maps to its first texel
+    let last_x = (tex_size.x as usize).max(1) - 1;
+    let last_y = (tex_size.y as usize).max(1) - 1;
+    Some((tx.min(last_x), ty.min(last_y)))
 }
 
-async fn generate_mask(texture_path: &str, width: usize, height: usize) -> Vec<u8> {
-    let image = load_image(texture_path).await.unwrap();
-    let pixels = image.bytes; // Image pixels in RGBA8 format
+// Reasons an image can fail to load
+#[allow(unused)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageError {
+    NotFound(String),                          // The file does not exist
+    Read { path: String, message: String },    // The file exists but could not be read (permissions, ...)
+    Decode { path: String, message: String },  // The file was read but is not a valid image
+    UnsupportedFormat(String),                 // The file is an image format this build cannot decode
+    ZeroSize(String),                          // The image has no pixels
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::NotFound(path) => write!(f, "image not found: {path}"),
+            ImageError::Read { path, message } => write!(f, "could not read image {path}: {message}"),
+            ImageError::Decode { path, message } => write!(f, "could not decode image {path}: {message}"),
+            ImageError::UnsupportedFormat(path) => write!(f, "unsupported image format: {path}"),
+            ImageError::ZeroSize(path) => write!(f, "image has zero width or height: {path}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+// Decode raw file bytes into an Image, sorting failures into ImageError variants
+pub fn decode_image(path: &str, bytes: &[u8]) -> Result<Image, ImageError> {
+    match Image::from_file_with_format(bytes, None) {
+        Ok(image) => Ok(image),
+        Err(macroquad::Error::ImageError(err)) => Err(convert_image_error(path, err)),
+        Err(err) => Err(ImageError::Decode {
+            path: path.to_string(),
+            message: err.to_string(),
+        }),
+    }
+}
+
+// Sort a load_file error into NotFound for missing files, or Read with the underlying message
+pub fn convert_load_error(path: &str, err: macroquad::Error) -> ImageError {
+    use macroquad::miniquad::fs::Error as FsError;
+    match err {
+        macroquad::Error::FileError { kind: FsError::IOError(err), .. } => convert_io_error(path, err),
+        macroquad::Error::FileError { kind: FsError::DownloadFailed | FsError::IOSAssetNoSuchFile, .. } => ImageError::NotFound(path.to_string()),
+        other => ImageError::Read {
+            path: path.to_string(),
+            message: other.to_string(),
+        },
+    }
+}
 
-    let mut mask = vec![0; (width * height + 7) / 8]; // Create a bitmask with enough bytes
+// Sort a std::io error into NotFound for missing files, or Read with the underlying message
+pub fn convert_io_error(path: &str, err: std::io::Error) -> ImageError {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => ImageError::NotFound(path.to_string()),
+        _ => ImageError::Read {
+            path: path.to_string(),
+            message: err.to_string(),
+        },
+    }
+}
 
-    for y in 0..height {
-        for x in 0..width {
-            let idx = (y * width + x) * 4; // Each pixel is 4 bytes (RGBA)
-            let alpha = pixels[idx + 3]; // Get alpha channel
-            let mask_byte_idx = (y * width + x) / 8; // Find which byte this pixel belongs to
-            let bit_offset = (y * width + x) % 8; // Find the bit offset inside the byte
+// Sort an error from the image crate into an ImageError variant
+fn convert_image_error(path: &str, err: image::ImageError) -> ImageError {
+    match err {
+        image::ImageError::Unsupported(_) => ImageError::UnsupportedFormat(path.to_string()),
+        other => ImageError::Decode {
+            path: path.to_string(),
+            message: other.to_string(),
+        },
+    }
+}
 
-            if alpha > 0 {
-                // Set the bit to 1 (opaque pixel)
-                mask[mask_byte_idx] |= 1 << (7 - bit_offset);
-            } else {
-                // Set the bit to 0 (transparent pixel)
-                mask[mask_byte_idx] &= !(1 << (7 - bit_offset));
+// Delay used for still images and for frames that ask for (almost) no delay,
+// matching how web browsers play such GIFs
+pub const DEFAULT_FRAME_DELAY: f32 = 0.1;
+
+// Decode every frame of an animated GIF or APNG along with its delay in seconds
+// Frames come back as full-size images with earlier frames already composited in
+// Any other image, including a PNG without animation, comes back as a single frame
+pub fn decode_frames(path: &str, bytes: &[u8]) -> Result<Vec<(Image, f32)>, ImageError> {
+    use image::AnimationDecoder;
+    use image::codecs::gif::GifDecoder;
+    use image::codecs::png::PngDecoder;
+    use std::io::Cursor;
+
+    let frames = match image::guess_format(bytes) {
+        Ok(image::ImageFormat::Gif) => GifDecoder::new(Cursor::new(bytes))
+            .and_then(|decoder| decoder.into_frames().collect_frames()),
+        Ok(image::ImageFormat::Png) => {
+            let decoder = PngDecoder::new(Cursor::new(bytes)).map_err(|err| convert_image_error(path, err))?;
+            if !decoder.is_apng() {
+                return Ok(vec![(decode_image(path, bytes)?, DEFAULT_FRAME_DELAY)]);
+            }
+            decoder.apng().into_frames().collect_frames()
+        }
+        _ => return Ok(vec![(decode_image(path, bytes)?, DEFAULT_FRAME_DELAY)]),
+    }
+    .map_err(|err| convert_image_error(path, err))?;
+
+    if frames.is_empty() {
+        return Err(ImageError::ZeroSize(path.to_string()));
+    }
+    Ok(frames
+        .into_iter()
+        .map(|frame| {
+            let (numerator, denominator) = frame.delay().numer_denom_ms();
+            let millis = numerator as f32 / denominator.max(1) as f32;
+            let delay = if millis < 10.0 { DEFAULT_FRAME_DELAY } else { millis / 1000.0 };
+            let buffer = frame.into_buffer();
+            let image = Image {
+                width: buffer.width() as u16,
+                height: buffer.height() as u16,
+                bytes: buffer.into_raw(),
+            };
+            (image, delay)
+        })
+        .collect())
+}
+
+// Filename prefix given to placeholder textures, followed by the path that failed
+pub const MISSING_PREFIX: &str = "__missing__:";
+
+// Generate a magenta and black checkerboard to stand in for an image that failed to load
+// The mask is fully opaque so the placeholder still takes part in hit-testing
+pub fn missing_placeholder(path: &str) -> PreloadedTexture {
+    const SIZE: u16 = 16;
+    const CELL: u16 = 4;
+    let mut image = Image::gen_image_color(SIZE, SIZE, BLACK);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            if (x / CELL + y / CELL).is_multiple_of(2) {
+                image.set_pixel(x as u32, y as u32, MAGENTA);
             }
         }
     }
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Nearest); // Keep the squares crisp when stretched
+    let mask = TransparencyMask::filled(SIZE as usize, SIZE as usize);
+    (texture, Rc::new(mask), format!("{MISSING_PREFIX}{path}").into(), None)
+}
 
-    mask
+// Build the transparency mask straight from decoded image data
+fn generate_mask(image: &Image, mask_settings: MaskSettings) -> TransparencyMask {
+    // Image pixels are in RGBA8 format
+    TransparencyMask::from_rgba_with(&image.bytes, image.width as usize, image.height as usize, mask_settings)
 }
-pub async fn set_texture_main(texture_path: &str) -> (Texture2D, Vec<u8>) {
-    let texture = load_texture(texture_path).await.unwrap();
+
+// Turn an already decoded image into a texture and matching mask
+// Shared by file loading and procedurally created images so both go through one pipeline
+// Panics on a zero-size image, use try_texture_from_image to handle that case
+pub fn texture_from_image(image: &Image, mask_settings: MaskSettings) -> (Texture2D, TransparencyMask) {
+    try_texture_from_image("__image__", image, mask_settings).unwrap_or_else(|err| panic!("{err}"))
+}
+
+// Fallible version of texture_from_image, the name is only used in the error
+pub fn try_texture_from_image(name: &str, image: &Image, mask_settings: MaskSettings) -> Result<(Texture2D, TransparencyMask), ImageError> {
+    if image.width == 0 || image.height == 0 {
+        return Err(ImageError::ZeroSize(name.to_string()));
+    }
+    let texture = Texture2D::from_image(image);
     texture.set_filter(FilterMode::Linear);
-    let tex_width = texture.width() as usize;
-    let tex_height = texture.height() as usize;
-    let transparency_mask = generate_mask(texture_path, tex_width, tex_height).await;
-    return (texture, transparency_mask);
+    let transparency_mask = generate_mask(image, mask_settings);
+    Ok((texture, transparency_mask))
 }
 
+// Decode the file once and derive both the texture and the mask from it
+// Panics if the image cannot be loaded, use try_set_texture_main to handle the error instead
+#[allow(unused)]
+pub async fn set_texture_main(texture_path: &str, mask_settings: MaskSettings) -> (Texture2D, TransparencyMask) {
+    try_set_texture_main(texture_path, mask_settings)
+        .await
+        .unwrap_or_else(|err| panic!("{err}"))
+}
+
+// Load an image file, returning an ImageError for missing, corrupt, unsupported or empty files
+pub async fn try_set_texture_main(texture_path: &str, mask_settings: MaskSettings) -> Result<(Texture2D, TransparencyMask), ImageError> {
+    let bytes = load_file(texture_path).await.map_err(|err| convert_load_error(texture_path, err))?;
+    try_texture_from_bytes(texture_path, &bytes, mask_settings)
+}
+
+// Decode encoded file data (PNG, JPEG, TGA, GIF) that is already in memory into a texture and mask
+// The name is only used in errors
+pub fn try_texture_from_bytes(name: &str, bytes: &[u8], mask_settings: MaskSettings) -> Result<(Texture2D, TransparencyMask), ImageError> {
+    let image = decode_image(name, bytes)?;
+    try_texture_from_image(name, &image, mask_settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 4x2 mask with only the left column and the bottom right pixel opaque
+    fn test_mask() -> TransparencyMask {
+        let mut pixels = vec![0u8; 4 * 2 * 4];
+        for (x, y) in [(0, 0), (0, 1), (3, 1)] {
+            pixels[(y * 4 + x) * 4 + 3] = 255;
+        }
+        TransparencyMask::from_rgba(&pixels, 4, 2)
+    }
+
+    #[test]
+    fn screen_to_texel_unstretched() {
+        let pos = vec2(100.0, 50.0);
+        let size = vec2(4.0, 2.0);
+        assert_eq!(screen_to_texel(vec2(100.0, 50.0), pos, size, size), Some((0, 0)));
+        assert_eq!(screen_to_texel(vec2(103.9, 51.9), pos, size, size), Some((3, 1)));
+        assert_eq!(screen_to_texel(vec2(102.5, 50.5), pos, size, size), Some((2, 0)));
+    }
+
+    #[test]
+    fn screen_to_texel_outside_is_none() {
+        let pos = vec2(100.0, 50.0);
+        let size = vec2(4.0, 2.0);
+        assert_eq!(screen_to_texel(vec2(99.9, 50.0), pos, size, size), None);
+        assert_eq!(screen_to_texel(vec2(100.0, 49.9), pos, size, size), None);
+        assert_eq!(screen_to_texel(vec2(104.0, 51.0), pos, size, size), None);
+        assert_eq!(screen_to_texel(vec2(101.0, 52.0), pos, size, size), None);
+        assert_eq!(screen_to_texel(vec2(0.0, 0.0), Vec2::ZERO, Vec2::ZERO, size), None);
+    }
+
+    #[test]
+    fn screen_to_texel_stretched() {
+        // A 4x2 texture stretched to 40x40
+        let tex_size = vec2(4.0, 2.0);
+        let draw_size = vec2(40.0, 40.0);
+        assert_eq!(screen_to_texel(vec2(9.9, 19.9), Vec2::ZERO, draw_size, tex_size), Some((0, 0)));
+        assert_eq!(screen_to_texel(vec2(10.0, 20.0), Vec2::ZERO, draw_size, tex_size), Some((1, 1)));
+        assert_eq!(screen_to_texel(vec2(39.99, 39.99), Vec2::ZERO, draw_size, tex_size), Some((3, 1)));
+    }
+
+    #[test]
+    fn screen_to_texel_zoomed() {
+        // A 4x2 texture at zoom 2.5 covers 10x5 screen pixels
+        let tex_size = vec2(4.0, 2.0);
+        let draw_size = tex_size * 2.5;
+        let pos = vec2(-5.0, 5.0);
+        assert_eq!(screen_to_texel(vec2(-2.6, 7.4), pos, draw_size, tex_size), Some((0, 0)));
+        assert_eq!(screen_to_texel(vec2(-2.5, 7.5), pos, draw_size, tex_size), Some((1, 1)));
+        assert_eq!(screen_to_texel(vec2(4.9, 9.9), pos, draw_size, tex_size), Some((3, 1)));
+        assert_eq!(screen_to_texel(vec2(5.0, 9.9), pos, draw_size, tex_size), None);
+    }
+
+    #[test]
+    fn screen_to_texel_sub_pixel_source() {
+        // A 64x64 image covering a 200x2 box only shows 0.64 texels of height
+        let visible = cover_crop(Rect::new(0.0, 0.0, 64.0, 64.0), vec2(200.0, 2.0), vec2(0.5, 0.5));
+        assert!(visible.h < 1.0);
+        let draw_size = vec2(200.0, 2.0);
+        assert_eq!(screen_to_texel(vec2(0.0, 0.0), Vec2::ZERO, draw_size, visible.size()), Some((0, 0)));
+        assert_eq!(screen_to_texel(vec2(199.9, 1.9), Vec2::ZERO, draw_size, visible.size()), Some((63, 0)));
+        assert_eq!(screen_to_texel(vec2(5.0, 1.0), Vec2::ZERO, draw_size, Vec2::ZERO), Some((0, 0)));
+    }
+
+    #[test]
+    fn mask_lookup_through_screen_to_texel() {
+        let mask = test_mask();
+        let tex_size = vec2(4.0, 2.0);
+        let hit = |point: Vec2, draw_size: Vec2| {
+            screen_to_texel(point, Vec2::ZERO, draw_size, tex_size).is_some_and(|(tx, ty)| mask.get(tx, ty))
+        };
+        // Unstretched
+        assert!(hit(vec2(0.5, 0.5), tex_size));
+        assert!(!hit(vec2(1.5, 0.5), tex_size));
+        assert!(hit(vec2(3.5, 1.5), tex_size));
+        assert!(!hit(vec2(3.5, 0.5), tex_size));
+        // Stretched to 8x8, each texel is 2x4 screen pixels
+        let stretched = vec2(8.0, 8.0);
+        assert!(hit(vec2(1.9, 7.9), stretched));
+        assert!(!hit(vec2(2.1, 7.9), stretched));
+        assert!(hit(vec2(6.0, 4.0), stretched));
+        assert!(!hit(vec2(6.0, 3.9), stretched));
+        // Outside the drawn area never hits
+        assert!(!hit(vec2(-0.1, 0.5), tex_size));
+        assert!(!hit(vec2(8.0, 7.9), stretched));
+    }
+
+    // Encode a small RGBA image as PNG file bytes
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let pixels = image::RgbaImage::from_pixel(width, height, image::Rgba([255, 0, 0, 255]));
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        pixels.write_to(&mut bytes, image::ImageOutputFormat::Png).unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn decode_image_valid_png() {
+        let image = decode_image("red.png", &png_bytes(3, 2)).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(&image.bytes[..4], &[255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn decode_image_garbage_is_an_error() {
+        // No known file signature, so the image crate can't even pick a decoder
+        let result = decode_image("x", b"garbage").map(|_| ());
+        assert_eq!(result, Err(ImageError::UnsupportedFormat("x".to_string())));
+    }
+
+    #[test]
+    fn missing_file_is_not_found() {
+        let path = std::env::temp_dir().join("image_test_missing_file.png");
+        let err = std::fs::read(&path).unwrap_err();
+        let err = macroquad::Error::FileError {
+            kind: err.into(),
+            path: path.to_str().unwrap().to_string(),
+        };
+        assert_eq!(convert_load_error("missing.png", err), ImageError::NotFound("missing.png".to_string()));
+    }
+
+    #[test]
+    fn other_read_errors_keep_their_message() {
+        let err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "access denied");
+        assert_eq!(
+            convert_io_error("locked.png", err),
+            ImageError::Read {
+                path: "locked.png".to_string(),
+                message: "access denied".to_string(),
+            }
+        );
+        let err = macroquad::Error::FileError {
+            kind: std::io::Error::other("disk on fire").into(),
+            path: "x".to_string(),
+        };
+        assert!(matches!(convert_load_error("x", err), ImageError::Read { message, .. } if message == "disk on fire"));
+    }
+
+    #[test]
+    fn decode_image_truncated_png_is_decode_error() {
+        let bytes = png_bytes(16, 16);
+        let truncated = &bytes[..bytes.len() / 2];
+        assert!(matches!(decode_image("cut.png", truncated), Err(ImageError::Decode { path, .. }) if path == "cut.png"));
+    }
+
+    #[test]
+    fn try_texture_from_image_zero_size() {
+        let image = Image::empty();
+        let result = try_texture_from_image("empty", &image, MaskSettings::default());
+        assert!(matches!(result, Err(ImageError::ZeroSize(name)) if name == "empty"));
+    }
+
+    #[test]
+    fn zoom_about_keeps_focal_point_fixed() {
+        assert_eq!(zoom_about(vec2(0.0, 0.0), vec2(50.0, 50.0), 1.0, 2.0), vec2(-50.0, -50.0));
+        assert_eq!(zoom_about(vec2(-50.0, -50.0), vec2(50.0, 50.0), 2.0, 1.0), vec2(0.0, 0.0));
+        // The focal point sits at the same fraction of the image before and after
+        let (top_left, focal, size) = (vec2(10.0, 20.0), vec2(70.0, 45.0), vec2(200.0, 100.0));
+        let new_top_left = zoom_about(top_left, focal, 1.5, 4.0);
+        let before = (focal - top_left) / (size * 1.5);
+        let after = (focal - new_top_left) / (size * 4.0);
+        assert!((before - after).length() < 1e-5);
+        // Zooming about the top-left corner doesn't move it
+        assert_eq!(zoom_about(top_left, top_left, 1.0, 3.0), top_left);
+    }
+
+    #[test]
+    fn zoom_about_ignores_invalid_old_zoom() {
+        assert_eq!(zoom_about(vec2(5.0, 5.0), vec2(50.0, 50.0), 0.0, 2.0), vec2(5.0, 5.0));
+    }
+
+    #[test]
+    fn clamp_to_view_larger_image_covers_view() {
+        let view = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let size = vec2(200.0, 200.0);
+        assert_eq!(clamp_to_view(vec2(10.0, -300.0), size, view), vec2(0.0, -100.0));
+        assert_eq!(clamp_to_view(vec2(-150.0, 5.0), size, view), vec2(-100.0, 0.0));
+        assert_eq!(clamp_to_view(vec2(-40.0, -60.0), size, view), vec2(-40.0, -60.0));
+    }
+
+    #[test]
+    fn clamp_to_view_smaller_image_stays_inside() {
+        let view = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let size = vec2(50.0, 50.0);
+        assert_eq!(clamp_to_view(vec2(-10.0, 90.0), size, view), vec2(0.0, 50.0));
+        assert_eq!(clamp_to_view(vec2(20.0, 30.0), size, view), vec2(20.0, 30.0));
+        // Each axis is clamped on its own
+        assert_eq!(clamp_to_view(vec2(-10.0, -10.0), vec2(200.0, 50.0), view), vec2(-10.0, 0.0));
+    }
+
+    #[test]
+    fn keep_overlapping_stops_a_quarter_inside() {
+        let view = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let size = vec2(200.0, 40.0);
+        // Dragged far right and down: a quarter of the view width, a quarter of the image height stays
+        assert_eq!(keep_overlapping(vec2(500.0, 500.0), size, view, 0.25), vec2(75.0, 90.0));
+        // Dragged far left and up: the same amounts hang over the other edges
+        assert_eq!(keep_overlapping(vec2(-500.0, -500.0), size, view, 0.25), vec2(-175.0, -30.0));
+        // Anywhere in between is left alone, even partly outside
+        assert_eq!(keep_overlapping(vec2(-120.0, 80.0), size, view, 0.25), vec2(-120.0, 80.0));
+    }
+
+    #[test]
+    fn keep_overlapping_with_no_minimum_allows_touching() {
+        let view = Rect::new(10.0, 10.0, 50.0, 50.0);
+        let size = vec2(20.0, 20.0);
+        assert_eq!(keep_overlapping(vec2(100.0, -100.0), size, view, 0.0), vec2(60.0, -10.0));
+    }
+
+    // An upright, unflipped, unclipped placement of a whole mask
+    fn placement(top_left: Vec2, draw_size: Vec2, mask: &TransparencyMask) -> MaskPlacement {
+        MaskPlacement {
+            pos: top_left,
+            rotation: 0.0,
+            clip: None,
+            top_left,
+            draw_size,
+            visible: Rect::new(0.0, 0.0, mask.width() as f32, mask.height() as f32),
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    fn assert_near(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-4, "{a} is not {b}");
+    }
+
+    #[test]
+    fn rotate_around_turns_clockwise_on_screen() {
+        let center = vec2(10.0, 10.0);
+        // A quarter turn takes a point to the right of the centre to below it (y points down)
+        assert_near(rotate_around(vec2(12.0, 10.0), center, std::f32::consts::FRAC_PI_2), vec2(10.0, 12.0));
+        assert_near(rotate_around(vec2(12.0, 10.0), center, std::f32::consts::PI), vec2(8.0, 10.0));
+        assert_eq!(rotate_around(vec2(3.0, 4.0), center, 0.0), vec2(3.0, 4.0));
+        // Rotating back undoes it
+        let turned = rotate_around(vec2(3.0, 4.0), center, 0.7);
+        assert_near(rotate_around(turned, center, -0.7), vec2(3.0, 4.0));
+    }
+
+    #[test]
+    fn rotated_bounds_cover_the_turned_corners() {
+        let rect = Rect::new(0.0, 0.0, 4.0, 2.0);
+        assert_eq!(rotated_bounds(rect, vec2(2.0, 1.0), 0.0), rect);
+        // A quarter turn around the centre swaps width and height
+        let quarter = rotated_bounds(rect, vec2(2.0, 1.0), std::f32::consts::FRAC_PI_2);
+        assert_near(quarter.point(), vec2(1.0, -1.0));
+        assert_near(quarter.size(), vec2(2.0, 4.0));
+        // An eighth turn of a square grows it by sqrt(2) around the same centre
+        let square = Rect::new(0.0, 0.0, 2.0, 2.0);
+        let eighth = rotated_bounds(square, vec2(1.0, 1.0), std::f32::consts::FRAC_PI_4);
+        assert_near(eighth.center(), vec2(1.0, 1.0));
+        assert_near(eighth.size(), Vec2::splat(2.0 * std::f32::consts::SQRT_2));
+        // Turning around a corner moves the box
+        let around_corner = rotated_bounds(square, Vec2::ZERO, std::f32::consts::FRAC_PI_2);
+        assert_near(around_corner.point(), vec2(-2.0, 0.0));
+    }
+
+    #[test]
+    fn flipped_placement_mirrors_texels() {
+        let mask = TransparencyMask::new(4, 2);
+        let mut flipped = placement(Vec2::ZERO, vec2(8.0, 4.0), &mask);
+        flipped.flip_x = true;
+        // The left screen column shows the rightmost texel and the right one the leftmost
+        assert_eq!(flipped.texel(vec2(0.5, 0.5)), Some((3, 0)));
+        assert_eq!(flipped.texel(vec2(7.5, 0.5)), Some((0, 0)));
+        flipped.flip_y = true;
+        assert_eq!(flipped.texel(vec2(0.5, 0.5)), Some((3, 1)));
+        assert_eq!(flipped.texel(vec2(7.5, 3.5)), Some((0, 0)));
+        // Flips apply within the visible part, then shift by its corner
+        let mut part = placement(Vec2::ZERO, vec2(4.0, 2.0), &mask);
+        part.visible = Rect::new(2.0, 0.0, 2.0, 2.0);
+        part.flip_x = true;
+        assert_eq!(part.texel(vec2(0.5, 0.5)), Some((3, 0)));
+        assert_eq!(part.texel(vec2(3.5, 0.5)), Some((2, 0)));
+        assert_eq!(part.texel(vec2(4.5, 0.5)), None);
+    }
+
+    #[test]
+    fn rotated_clip_hits_only_inside_the_turned_box() {
+        let mask = TransparencyMask::filled(4, 4);
+        // A 40x40 image centred on a 20x20 clip box, both turned an eighth around the centre
+        let mut turned = placement(vec2(-20.0, -20.0), vec2(40.0, 40.0), &mask);
+        turned.pos = Vec2::ZERO;
+        turned.rotation = std::f32::consts::FRAC_PI_4;
+        turned.clip = Some(Rect::new(-10.0, -10.0, 20.0, 20.0));
+        // The corner of the upright bounds of the turned box is outside the box itself
+        assert!(turned.texel(vec2(13.0, 13.0)).is_none());
+        // Along the axes the turned box reaches out to 10 * sqrt(2)
+        assert!(turned.texel(vec2(13.0, 0.0)).is_some());
+        assert!(turned.texel(vec2(15.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn clip_draw_crops_dest_and_source_together() {
+        let dest = Rect::new(0.0, 0.0, 100.0, 50.0);
+        let source = Rect::new(10.0, 20.0, 20.0, 10.0);
+        let area = Rect::new(25.0, 0.0, 50.0, 25.0);
+        let (cropped_dest, cropped_source) = clip_draw(dest, source, area, false, false).unwrap();
+        assert_eq!(cropped_dest, area);
+        assert_eq!(cropped_source, Rect::new(15.0, 20.0, 10.0, 5.0));
+        // Fully inside is left alone
+        assert_eq!(clip_draw(dest, source, Rect::new(-10.0, -10.0, 200.0, 200.0), false, false), Some((dest, source)));
+        // Outside, or only touching an edge, draws nothing
+        assert_eq!(clip_draw(dest, source, Rect::new(200.0, 0.0, 10.0, 10.0), false, false), None);
+        assert_eq!(clip_draw(dest, source, Rect::new(100.0, 0.0, 10.0, 10.0), false, false), None);
+    }
+
+    #[test]
+    fn clip_draw_mirrors_flipped_crops() {
+        let dest = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let source = Rect::new(0.0, 0.0, 10.0, 10.0);
+        // Keeping the left quarter of a flipped image keeps the right quarter of its texture
+        let left = Rect::new(0.0, 0.0, 25.0, 100.0);
+        assert_eq!(clip_draw(dest, source, left, true, false), Some((left, Rect::new(7.5, 0.0, 2.5, 10.0))));
+        let top = Rect::new(0.0, 0.0, 100.0, 25.0);
+        assert_eq!(clip_draw(dest, source, top, false, true), Some((top, Rect::new(0.0, 7.5, 10.0, 2.5))));
+        assert_eq!(clip_draw(dest, source, top, false, false), Some((top, Rect::new(0.0, 0.0, 10.0, 2.5))));
+    }
+
+    fn drawn_rect(placement: &MaskPlacement) -> Rect {
+        Rect::new(placement.top_left.x, placement.top_left.y, placement.draw_size.x, placement.draw_size.y)
+    }
+
+    #[test]
+    fn mask_overlap_stretched_pair() {
+        // Left half of a 4x4 mask is opaque, stretched to 8x8: opaque screen columns 0 to 3
+        let mut left_half = TransparencyMask::new(4, 4);
+        for y in 0..4 {
+            left_half.set(0, y, true);
+            left_half.set(1, y, true);
+        }
+        let a = placement(Vec2::ZERO, vec2(8.0, 8.0), &left_half);
+        // A solid 2x2 mask stretched to 4x4
+        let solid = TransparencyMask::filled(2, 2);
+        let b = placement(vec2(3.0, 0.0), vec2(4.0, 4.0), &solid);
+        let area = drawn_rect(&a).intersect(drawn_rect(&b)).unwrap();
+        // Only screen column 3 is opaque in both, for 4 rows
+        assert_eq!(mask_overlap((&left_half, &a), (&solid, &b), area, false), 4);
+        assert_eq!(mask_overlap((&left_half, &a), (&solid, &b), area, true), 1);
+        // One pixel further right the solid image only touches the transparent half
+        let b = placement(vec2(4.0, 0.0), vec2(4.0, 4.0), &solid);
+        let area = drawn_rect(&a).intersect(drawn_rect(&b)).unwrap();
+        assert_eq!(mask_overlap((&left_half, &a), (&solid, &b), area, false), 0);
+    }
+
+    #[test]
+    fn mask_overlap_zoomed_pair() {
+        // Only texel (1, 1) of a 2x2 mask is opaque; at zoom 3 it covers screen 13..16
+        let mut corner = TransparencyMask::new(2, 2);
+        corner.set(1, 1, true);
+        let a = placement(vec2(10.0, 10.0), vec2(2.0, 2.0) * 3.0, &corner);
+        // A solid 1x1 mask at zoom 2 covering screen 15..17
+        let dot = TransparencyMask::filled(1, 1);
+        let b = placement(vec2(15.0, 15.0), vec2(1.0, 1.0) * 2.0, &dot);
+        let area = drawn_rect(&a).intersect(drawn_rect(&b)).unwrap();
+        assert_eq!(mask_overlap((&corner, &a), (&dot, &b), area, false), 1);
+        // Moved over the transparent texel (0, 0) there is no contact
+        let b = placement(vec2(10.0, 10.0), vec2(1.0, 1.0) * 2.0, &dot);
+        let area = drawn_rect(&a).intersect(drawn_rect(&b)).unwrap();
+        assert_eq!(mask_overlap((&corner, &a), (&dot, &b), area, false), 0);
+    }
+}
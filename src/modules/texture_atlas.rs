@@ -0,0 +1,484 @@
+/*
+Made by: image_test contributors
+Date: 2026-10-18
+Program Details: Packs many small images into a few large textures to cut down on texture switches
+
+To import you need:
+In the mod modules section add:
+    pub mod texture_atlas;
+    pub mod transparency_mask;
+
+You normally don't use this directly. Turn on atlas mode in the TextureManager
+before preloading and small images will be packed for you:
+    texture_manager.enable_atlas(2048); // Pages are 2048 x 2048 pixels
+    texture_manager.preload_all(&["assets/coin.png", "assets/gem.png"]).await;
+
+Each packed image is handed out as the shared page texture plus a source
+rectangle, and StillImage::draw only draws that rectangle. Masks stay per-image.
+Images too big to fit on an empty page are loaded as their own texture as usual.
+Unloading or reloading an image gives its space back for later images once no
+StillImage shows the old copy any more, and a page is released once nothing is
+left on it. Page sizes are capped at MAX_PAGE_SIZE.
+
+Using the packer on its own (for example to lay out your own sprite sheet):
+    let mut packer = SkylinePacker::new(256, 256);
+    if let Some((x, y)) = packer.insert(32, 48) {
+        println!("Placed at {}, {}", x, y);
+    }
+*/
+use crate::modules::transparency_mask::TransparencyMask;
+use macroquad::prelude::*;
+use std::rc::{Rc, Weak};
+
+// Transparent-safe gap around each packed image
+// The image's edge pixels are copied into it so linear filtering doesn't bleed neighbours in
+const PADDING: u32 = 1;
+
+// Bottom-left skyline rectangle packer
+// The skyline is the top edge of everything packed so far, stored as flat segments
+pub struct SkylinePacker {
+    width: u32,
+    height: u32,
+    skyline: Vec<(u32, u32, u32)>, // (x, y, width) of each segment, left to right
+}
+
+impl SkylinePacker {
+    // Create a packer for an empty area of the given size
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            skyline: vec![(0, 0, width)],
+        }
+    }
+
+    // Find room for a width x height rectangle and reserve it
+    // Returns the top-left corner, or None if there is no room left
+    pub fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+        // Pick the spot that keeps the skyline lowest, then the leftmost one
+        let mut best: Option<(usize, u32, u32)> = None; // (segment index, x, y)
+        for index in 0..self.skyline.len() {
+            if let Some(y) = self.fits(index, width, height) {
+                let x = self.skyline[index].0;
+                if best.is_none_or(|(_, best_x, best_y)| (y, x) < (best_y, best_x)) {
+                    best = Some((index, x, y));
+                }
+            }
+        }
+        let (index, x, y) = best?;
+        self.add_segment(index, x, y + height, width);
+        Some((x, y))
+    }
+
+    // Height a rectangle would rest at if its left edge sits on the given segment
+    fn fits(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.skyline[index].0;
+        if x + width > self.width {
+            return None;
+        }
+        let mut y = 0;
+        let mut width_left = width;
+        let mut i = index;
+        while width_left > 0 {
+            let (_, segment_y, segment_width) = *self.skyline.get(i)?;
+            y = y.max(segment_y);
+            if y + height > self.height {
+                return None;
+            }
+            width_left = width_left.saturating_sub(segment_width);
+            i += 1;
+        }
+        Some(y)
+    }
+
+    // Raise the skyline over [x, x + width) to the new top, trimming the segments underneath
+    fn add_segment(&mut self, index: usize, x: u32, top: u32, width: u32) {
+        self.skyline.insert(index, (x, top, width));
+        let right = x + width;
+        let i = index + 1;
+        while i < self.skyline.len() {
+            let (segment_x, segment_y, segment_width) = self.skyline[i];
+            if segment_x >= right {
+                break;
+            }
+            let segment_right = segment_x + segment_width;
+            if segment_right <= right {
+                self.skyline.remove(i);
+            } else {
+                self.skyline[i] = (right, segment_y, segment_right - right);
+                break;
+            }
+        }
+        // Merge neighbours at the same height to keep the list short
+        let mut i = 0;
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].1 == self.skyline[i + 1].1 {
+                self.skyline[i].2 += self.skyline[i + 1].2;
+                self.skyline.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+// Largest page side allowed. Bigger pages would not fit in a u16 texture size,
+// and most GPUs can't make textures this large anyway
+pub const MAX_PAGE_SIZE: u32 = 8192;
+
+// Where an image was packed: its page and the padded area reserved for it
+// Hand it back to TextureAtlas::remove when the image is unloaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasRegion {
+    page: usize,
+    x: u32,
+    y: u32,
+    width: u32, // Including PADDING on both sides
+    height: u32,
+}
+
+// Free space on one page: a skyline for never-used space plus a list of areas given back
+// Kept apart from the page texture so the bookkeeping works (and can be tested) without a GPU
+struct PageSpace {
+    packer: SkylinePacker,
+    free: Vec<(u32, u32, u32, u32)>, // (x, y, width, height) areas given back by release, reused first
+    live: usize,                     // Areas currently reserved
+}
+
+impl PageSpace {
+    fn new(size: u32) -> Self {
+        Self {
+            packer: SkylinePacker::new(size, size),
+            free: Vec::new(),
+            live: 0,
+        }
+    }
+
+    // Reserve a width x height area, preferring the smallest freed area it fits in
+    fn reserve(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let best_free = self
+            .free
+            .iter()
+            .enumerate()
+            .filter(|(_, area)| area.2 >= width && area.3 >= height)
+            .min_by_key(|(_, area)| area.2 * area.3)
+            .map(|(index, _)| index);
+        let position = match best_free {
+            Some(index) => {
+                // Use the top-left corner and give back the strips to the right and below
+                let (x, y, free_width, free_height) = self.free.swap_remove(index);
+                if free_width > width {
+                    self.free.push((x + width, y, free_width - width, height));
+                }
+                if free_height > height {
+                    self.free.push((x, y + height, free_width, free_height - height));
+                }
+                (x, y)
+            }
+            None => self.packer.insert(width, height)?,
+        };
+        self.live += 1;
+        Some(position)
+    }
+
+    // Give a reserved area back. Returns true once nothing is reserved on the page
+    fn release(&mut self, x: u32, y: u32, width: u32, height: u32) -> bool {
+        self.live = self.live.saturating_sub(1);
+        if self.live > 0 {
+            self.free.push((x, y, width, height));
+        }
+        self.live == 0
+    }
+}
+
+// One atlas texture and the space left on it
+struct AtlasPage {
+    texture: Texture2D,
+    space: PageSpace,
+}
+
+impl AtlasPage {
+    fn new(size: u32) -> Self {
+        let side = size as u16; // Fits, TextureAtlas::new caps the size at MAX_PAGE_SIZE
+        let texture = Texture2D::from_rgba8(side, side, &vec![0; size as usize * size as usize * 4]);
+        texture.set_filter(FilterMode::Linear);
+        Self {
+            texture,
+            space: PageSpace::new(size),
+        }
+    }
+}
+
+// A growing set of atlas pages
+pub struct TextureAtlas {
+    page_size: u32,
+    pages: Vec<Option<AtlasPage>>, // None once every image on a page is removed, so indexes stay stable
+    retired: Vec<(AtlasRegion, Weak<TransparencyMask>)>, // Regions waiting for their last user to let go
+}
+
+impl TextureAtlas {
+    // Create an empty atlas whose pages are page_size x page_size pixels
+    // Sizes above MAX_PAGE_SIZE are capped to it
+    pub fn new(page_size: u32) -> Self {
+        Self {
+            page_size: page_size.min(MAX_PAGE_SIZE),
+            pages: Vec::new(),
+            retired: Vec::new(),
+        }
+    }
+
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    // Number of pages that currently hold at least one image
+    #[allow(unused)]
+    pub fn page_count(&self) -> usize {
+        self.pages.iter().flatten().count()
+    }
+
+    // Copy an image onto a page, opening a new page if none has room
+    // Returns the page texture, the image's source rectangle on it and the region to remove it with,
+    // or None if the image is too big to fit on even an empty page
+    pub fn add(&mut self, image: &Image) -> Option<(Texture2D, Rect, AtlasRegion)> {
+        self.collect_unused();
+        let padded_width = image.width as u32 + PADDING * 2;
+        let padded_height = image.height as u32 + PADDING * 2;
+        if padded_width > self.page_size || padded_height > self.page_size {
+            return None;
+        }
+
+        let placed = self.pages.iter_mut().enumerate().find_map(|(index, page)| {
+            let position = page.as_mut()?.space.reserve(padded_width, padded_height)?;
+            Some((index, position))
+        });
+        let (page, (x, y)) = match placed {
+            Some(found) => found,
+            None => {
+                let mut page = AtlasPage::new(self.page_size);
+                let position = page.space.reserve(padded_width, padded_height)?;
+                // Fill the gap left by a released page before growing the list
+                let index = match self.pages.iter().position(Option::is_none) {
+                    Some(index) => index,
+                    None => {
+                        self.pages.push(None);
+                        self.pages.len() - 1
+                    }
+                };
+                self.pages[index] = Some(page);
+                (index, position)
+            }
+        };
+
+        let region = AtlasRegion {
+            page,
+            x,
+            y,
+            width: padded_width,
+            height: padded_height,
+        };
+        let (texture, source) = self.write(region, image)?;
+        Some((texture, source, region))
+    }
+
+    // Draw a new image into an existing region, for reloading an image in place
+    // Returns None, leaving the region untouched, if the image is bigger than the region
+    // Only safe once nothing draws the old image, since they share the pixels
+    pub fn replace(&mut self, region: AtlasRegion, image: &Image) -> Option<(Texture2D, Rect)> {
+        if image.width as u32 + PADDING * 2 > region.width || image.height as u32 + PADDING * 2 > region.height {
+            return None;
+        }
+        self.write(region, image)
+    }
+
+    // Give a region back so its space can be reused
+    // A page with no images left is dropped, freeing its texture once nothing else holds it
+    // Only call this once nothing draws from the region any more, see remove_when_unused
+    pub fn remove(&mut self, region: AtlasRegion) {
+        let Some(slot) = self.pages.get_mut(region.page) else {
+            return;
+        };
+        let Some(page) = slot.as_mut() else {
+            return;
+        };
+        if page.space.release(region.x, region.y, region.width, region.height) {
+            *slot = None;
+        }
+    }
+
+    // Give a region back once the last copy of the image's mask is dropped
+    // StillImages hold the mask alongside the page texture and source rectangle, so while any of
+    // them still shows the image its pixels must stay put. Checked each time an image is added
+    pub fn remove_when_unused(&mut self, region: AtlasRegion, mask: &Rc<TransparencyMask>) {
+        self.retired.push((region, Rc::downgrade(mask)));
+    }
+
+    // Remove every retired region whose image is no longer shown anywhere
+    fn collect_unused(&mut self) {
+        let (unused, in_use): (Vec<_>, Vec<_>) = std::mem::take(&mut self.retired).into_iter().partition(|(_, mask)| mask.strong_count() == 0);
+        self.retired = in_use;
+        for (region, _) in unused {
+            self.remove(region);
+        }
+    }
+
+    // Copy an image and its extruded border into a region's top-left corner
+    fn write(&self, region: AtlasRegion, image: &Image) -> Option<(Texture2D, Rect)> {
+        let page = self.pages.get(region.page)?.as_ref()?;
+        let padded = extrude(image);
+        page.texture.update_part(&padded, region.x as i32, region.y as i32, padded.width as i32, padded.height as i32);
+        let source = Rect::new(
+            (region.x + PADDING) as f32,
+            (region.y + PADDING) as f32,
+            image.width as f32,
+            image.height as f32,
+        );
+        Some((page.texture.clone(), source))
+    }
+}
+
+// Surround an image with a PADDING wide border that repeats its edge pixels
+fn extrude(image: &Image) -> Image {
+    let width = image.width as u32;
+    let height = image.height as u32;
+    let padded_width = width + PADDING * 2;
+    let padded_height = height + PADDING * 2;
+    let mut bytes = vec![0; (padded_width * padded_height * 4) as usize];
+    for y in 0..padded_height {
+        // Clamp into the source image so the border copies the nearest edge pixel
+        let source_y = y.saturating_sub(PADDING).min(height - 1);
+        for x in 0..padded_width {
+            let source_x = x.saturating_sub(PADDING).min(width - 1);
+            let from = ((source_y * width + source_x) * 4) as usize;
+            let to = ((y * padded_width + x) * 4) as usize;
+            bytes[to..to + 4].copy_from_slice(&image.bytes[from..from + 4]);
+        }
+    }
+    Image {
+        bytes,
+        width: padded_width as u16,
+        height: padded_height as u16,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Small deterministic pseudo-random sequence so failures can be reproduced
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, max: u32) -> u32 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) % max as u64) as u32 + 1
+        }
+    }
+
+    fn overlaps(a: (u32, u32, u32, u32), b: (u32, u32, u32, u32)) -> bool {
+        a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3
+    }
+
+    #[test]
+    fn packed_rects_never_overlap() {
+        for seed in 0..8 {
+            let mut random = Lcg(seed);
+            let mut packer = SkylinePacker::new(512, 512);
+            let mut placed: Vec<(u32, u32, u32, u32)> = Vec::new();
+            for _ in 0..400 {
+                let (width, height) = (random.next(64), random.next(64));
+                if let Some((x, y)) = packer.insert(width, height) {
+                    assert!(x + width <= 512 && y + height <= 512, "({x}, {y}) {width}x{height} is out of bounds");
+                    placed.push((x, y, width, height));
+                }
+            }
+            assert!(placed.len() > 50, "seed {seed} only packed {} rects", placed.len());
+            for (i, a) in placed.iter().enumerate() {
+                for b in &placed[i + 1..] {
+                    assert!(!overlaps(*a, *b), "seed {seed}: {a:?} overlaps {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reused_space_never_overlaps() {
+        for seed in 0..8 {
+            let mut random = Lcg(seed);
+            let mut space = PageSpace::new(256);
+            let mut live: Vec<(u32, u32, u32, u32)> = Vec::new();
+            let mut reused = 0;
+            for step in 0..2000 {
+                // Mostly add, sometimes give back a random live area so the free list gets used
+                if !live.is_empty() && random.next(3) == 1 {
+                    let (x, y, width, height) = live.swap_remove(random.next(live.len() as u32) as usize - 1);
+                    let empty = space.release(x, y, width, height);
+                    assert_eq!(empty, live.is_empty(), "seed {seed} step {step}");
+                    continue;
+                }
+                let (width, height) = (random.next(40), random.next(40));
+                let skyline_before = space.packer.skyline.clone();
+                if let Some((x, y)) = space.reserve(width, height) {
+                    assert!(x + width <= 256 && y + height <= 256, "({x}, {y}) {width}x{height} is out of bounds");
+                    let area = (x, y, width, height);
+                    for other in &live {
+                        assert!(!overlaps(area, *other), "seed {seed} step {step}: {area:?} overlaps {other:?}");
+                    }
+                    if space.packer.skyline == skyline_before {
+                        reused += 1; // Came from the free list
+                    }
+                    live.push(area);
+                }
+                assert_eq!(space.live, live.len());
+            }
+            assert!(reused > 0, "seed {seed} never reused freed space");
+        }
+    }
+
+    #[test]
+    fn release_frees_the_page_when_empty() {
+        let mut space = PageSpace::new(64);
+        let a = space.reserve(10, 10).unwrap();
+        let b = space.reserve(20, 20).unwrap();
+        assert!(!space.release(a.0, a.1, 10, 10));
+        // The smaller request goes in the freed corner instead of new skyline space
+        assert_eq!(space.reserve(5, 5), Some(a));
+        assert!(!space.release(b.0, b.1, 20, 20));
+        assert!(space.release(a.0, a.1, 5, 5));
+    }
+
+    #[test]
+    fn retired_regions_wait_for_their_mask() {
+        let mut atlas = TextureAtlas::new(64);
+        let region = AtlasRegion {
+            page: 0,
+            x: 0,
+            y: 0,
+            width: 4,
+            height: 4,
+        };
+        let mask = Rc::new(TransparencyMask::new(2, 2));
+        let shown = Rc::clone(&mask); // A StillImage still showing the old image
+        atlas.remove_when_unused(region, &mask);
+        drop(mask);
+        atlas.collect_unused();
+        assert_eq!(atlas.retired.len(), 1);
+        drop(shown);
+        atlas.collect_unused();
+        assert!(atlas.retired.is_empty());
+    }
+
+    #[test]
+    fn insert_rejects_empty_and_oversized() {
+        let mut packer = SkylinePacker::new(64, 64);
+        assert_eq!(packer.insert(0, 10), None);
+        assert_eq!(packer.insert(10, 0), None);
+        assert_eq!(packer.insert(65, 1), None);
+        assert_eq!(packer.insert(1, 65), None);
+        assert_eq!(packer.insert(64, 64), Some((0, 0)));
+        assert_eq!(packer.insert(1, 1), None);
+    }
+}
@@ -0,0 +1,415 @@
+/*
+Made by: image_test contributors
+Date: 2026-10-18
+Program Details: Typed per-pixel transparency mask used for hit-testing and collisions
+
+To import you need:
+In the mod modules section add:
+    pub mod transparency_mask;
+
+Then add the following with the use commands:
+use crate::modules::transparency_mask::{TransparencyMask, MaskSettings, MaskMode};
+
+By default the mask stores one bit per pixel, row-major, most significant bit first.
+A set bit means the pixel is opaque. You normally get one from a StillImage
+or the TextureManager rather than building it yourself:
+    let mask = img.get_mask();
+    if mask.get(10, 20) {
+        println!("Pixel (10, 20) is solid");
+    }
+
+Building a mask by hand:
+    let mut mask = TransparencyMask::new(32, 32); // All transparent
+    mask.set(5, 5, true);
+
+Other helpers:
+- Size: width(), height()
+- Count solid pixels: count_opaque()
+- Smallest rectangle holding every solid pixel: bounding_box()
+- Walk the mask a row at a time: for row in mask.iter_rows() { ... }
+- Combine two masks of the same size: and(), or(), xor()
+  These return None if the sizes do not match
+
+Alpha threshold and mask modes:
+By default any pixel with alpha above 0 counts as solid. Soft edges and drop
+shadows can be left out of hitboxes by raising the threshold:
+    let settings = MaskSettings::threshold(128); // Alpha must be above 128
+The Alpha mode keeps the full 8-bit alpha of every pixel instead of one bit.
+get() still answers with the threshold applied, and alpha() returns the raw value:
+    let settings = MaskSettings { alpha_threshold: 64, mode: MaskMode::Alpha };
+Pass the settings to StillImage::new_with_mask, StillImage::set_texture_with_mask
+or TextureManager::preload_with_mask.
+*/
+use macroquad::prelude::Rect;
+
+// How the mask stores each pixel
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskMode {
+    #[default]
+    Bitmask, // One bit per pixel, threshold applied when the mask is built
+    Alpha,   // Full 8-bit alpha per pixel, threshold applied when queried
+}
+
+// Options used when building a mask from image data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MaskSettings {
+    pub alpha_threshold: u8, // Pixels with alpha above this value count as opaque
+    pub mode: MaskMode,
+}
+
+#[allow(unused)]
+impl MaskSettings {
+    // Bitmask settings with a custom alpha threshold
+    pub fn threshold(alpha_threshold: u8) -> Self {
+        Self {
+            alpha_threshold,
+            mode: MaskMode::Bitmask,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MaskData {
+    Bits(Vec<u8>),  // One bit per pixel, row-major, MSB first
+    Alpha(Vec<u8>), // One alpha byte per pixel, row-major
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransparencyMask {
+    width: usize,
+    height: usize,
+    alpha_threshold: u8, // Threshold the mask was built with, Alpha masks also apply it when queried
+    data: MaskData,
+}
+
+#[allow(unused)]
+impl TransparencyMask {
+    // Create a fully transparent mask of the given size
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            alpha_threshold: 0,
+            data: MaskData::Bits(vec![0; (width * height).div_ceil(8)]),
+        }
+    }
+
+    // Create a fully opaque mask of the given size
+    pub fn filled(width: usize, height: usize) -> Self {
+        let mut mask = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                mask.set(x, y, true);
+            }
+        }
+        mask
+    }
+
+    // Build a mask from RGBA8 pixel data, treating any alpha above zero as opaque
+    pub fn from_rgba(pixels: &[u8], width: usize, height: usize) -> Self {
+        Self::from_rgba_with(pixels, width, height, MaskSettings::default())
+    }
+
+    // Build a mask from RGBA8 pixel data using the given threshold and mode
+    // Panics if pixels holds fewer than width * height RGBA pixels
+    pub fn from_rgba_with(pixels: &[u8], width: usize, height: usize, settings: MaskSettings) -> Self {
+        assert!(
+            pixels.len() >= width * height * 4,
+            "mask of {width}x{height} needs {} bytes of RGBA data, got {}",
+            width * height * 4,
+            pixels.len()
+        );
+        match settings.mode {
+            MaskMode::Bitmask => {
+                let mut mask = Self::new(width, height);
+                mask.alpha_threshold = settings.alpha_threshold;
+                for y in 0..height {
+                    for x in 0..width {
+                        let idx = (y * width + x) * 4; // Each pixel is 4 bytes (RGBA)
+                        let alpha = pixels[idx + 3]; // Get alpha channel
+                        if alpha > settings.alpha_threshold {
+                            mask.set(x, y, true);
+                        }
+                    }
+                }
+                mask
+            }
+            MaskMode::Alpha => Self {
+                width,
+                height,
+                alpha_threshold: settings.alpha_threshold,
+                data: MaskData::Alpha(pixels.chunks_exact(4).take(width * height).map(|px| px[3]).collect()),
+            },
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn mode(&self) -> MaskMode {
+        match self.data {
+            MaskData::Bits(_) => MaskMode::Bitmask,
+            MaskData::Alpha(_) => MaskMode::Alpha,
+        }
+    }
+
+    // The threshold and mode this mask was built with, to build a matching mask for new pixels
+    pub fn settings(&self) -> MaskSettings {
+        MaskSettings {
+            alpha_threshold: self.alpha_threshold,
+            mode: self.mode(),
+        }
+    }
+
+    // Raw storage, for code that needs the underlying bytes
+    // Bitmask masks return packed bits, Alpha masks return one byte per pixel
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.data {
+            MaskData::Bits(bits) => bits,
+            MaskData::Alpha(alpha) => alpha,
+        }
+    }
+
+    // Check if a pixel is opaque. Pixels outside the mask count as transparent
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let idx = y * self.width + x;
+        match &self.data {
+            MaskData::Bits(bits) => bits[idx / 8] & (1 << (7 - idx % 8)) != 0,
+            MaskData::Alpha(alpha) => alpha[idx] > self.alpha_threshold,
+        }
+    }
+
+    // Alpha value of a pixel. Bitmask masks report 255 for opaque and 0 for transparent
+    pub fn alpha(&self, x: usize, y: usize) -> u8 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
+        match &self.data {
+            MaskData::Bits(_) => {
+                if self.get(x, y) {
+                    255
+                } else {
+                    0
+                }
+            }
+            MaskData::Alpha(alpha) => alpha[y * self.width + x],
+        }
+    }
+
+    // Mark a pixel as opaque (true) or transparent (false). Out of range writes are ignored
+    // Alpha masks store 255 or 0
+    pub fn set(&mut self, x: usize, y: usize, opaque: bool) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let idx = y * self.width + x; // Find which bit this pixel belongs to
+        match &mut self.data {
+            MaskData::Bits(bits) => {
+                let bit = 1 << (7 - idx % 8); // Find the bit offset inside the byte
+                if opaque {
+                    bits[idx / 8] |= bit;
+                } else {
+                    bits[idx / 8] &= !bit;
+                }
+            }
+            MaskData::Alpha(alpha) => alpha[idx] = if opaque { 255 } else { 0 },
+        }
+    }
+
+    // Number of opaque pixels in the mask
+    pub fn count_opaque(&self) -> usize {
+        (0..self.height)
+            .map(|y| (0..self.width).filter(|&x| self.get(x, y)).count())
+            .sum()
+    }
+
+    // Smallest rectangle (in pixels) holding every opaque pixel, or None if the mask is empty
+    pub fn bounding_box(&self) -> Option<Rect> {
+        let mut min_x = usize::MAX;
+        let mut min_y = usize::MAX;
+        let mut max_x = 0;
+        let mut max_y = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) {
+                    min_x = min_x.min(x);
+                    min_y = min_y.min(y);
+                    max_x = max_x.max(x);
+                    max_y = max_y.max(y);
+                }
+            }
+        }
+        if min_x == usize::MAX {
+            return None;
+        }
+        Some(Rect::new(
+            min_x as f32,
+            min_y as f32,
+            (max_x - min_x + 1) as f32,
+            (max_y - min_y + 1) as f32,
+        ))
+    }
+
+    // Iterate over the mask one row at a time, top to bottom
+    pub fn iter_rows(&self) -> impl Iterator<Item = Vec<bool>> + '_ {
+        (0..self.height).map(move |y| (0..self.width).map(|x| self.get(x, y)).collect())
+    }
+
+    // Pixels opaque in both masks
+    pub fn and(&self, other: &TransparencyMask) -> Option<TransparencyMask> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    // Pixels opaque in either mask
+    pub fn or(&self, other: &TransparencyMask) -> Option<TransparencyMask> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    // Pixels opaque in exactly one of the masks
+    pub fn xor(&self, other: &TransparencyMask) -> Option<TransparencyMask> {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    // Apply a bitwise operation to two masks of the same size
+    // The result is always a Bitmask, with each Alpha mask's threshold already applied
+    fn combine(&self, other: &TransparencyMask, op: impl Fn(u8, u8) -> u8) -> Option<TransparencyMask> {
+        if self.width != other.width || self.height != other.height {
+            return None;
+        }
+        if let (MaskData::Bits(a), MaskData::Bits(b)) = (&self.data, &other.data) {
+            // Fast path: combine whole bytes at once
+            return Some(TransparencyMask {
+                width: self.width,
+                height: self.height,
+                alpha_threshold: 0,
+                data: MaskData::Bits(a.iter().zip(b).map(|(&a, &b)| op(a, b)).collect()),
+            });
+        }
+        let mut result = TransparencyMask::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let bit = op(self.get(x, y) as u8, other.get(x, y) as u8) & 1;
+                result.set(x, y, bit == 1);
+            }
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RGBA pixels with the given alpha values and black colour
+    fn rgba(alphas: &[u8]) -> Vec<u8> {
+        alphas.iter().flat_map(|&alpha| [0, 0, 0, alpha]).collect()
+    }
+
+    #[test]
+    fn bits_are_row_major_msb_first() {
+        let mut mask = TransparencyMask::new(3, 3);
+        mask.set(0, 0, true); // Pixel 0: top bit of byte 0
+        mask.set(1, 2, true); // Pixel 7: lowest bit of byte 0
+        mask.set(2, 2, true); // Pixel 8: top bit of byte 1
+        assert_eq!(mask.as_bytes(), &[0b1000_0001, 0b1000_0000]);
+        mask.set(0, 0, false);
+        assert_eq!(mask.as_bytes(), &[0b0000_0001, 0b1000_0000]);
+        assert!(mask.get(1, 2) && mask.get(2, 2) && !mask.get(0, 0));
+    }
+
+    #[test]
+    fn out_of_range_get_and_set() {
+        let mut mask = TransparencyMask::filled(2, 2);
+        assert!(!mask.get(2, 0));
+        assert!(!mask.get(0, 2));
+        assert_eq!(mask.alpha(5, 5), 0);
+        // Ignored rather than wrapping onto the next row
+        mask.set(2, 0, false);
+        mask.set(0, 7, false);
+        assert_eq!(mask.count_opaque(), 4);
+        let mut empty = TransparencyMask::new(2, 2);
+        empty.set(2, 0, true);
+        assert_eq!(empty.count_opaque(), 0);
+    }
+
+    #[test]
+    fn bounding_box_empty_and_single_pixel() {
+        let mut mask = TransparencyMask::new(5, 4);
+        assert_eq!(mask.bounding_box(), None);
+        mask.set(3, 2, true);
+        assert_eq!(mask.bounding_box(), Some(Rect::new(3.0, 2.0, 1.0, 1.0)));
+        mask.set(1, 0, true);
+        assert_eq!(mask.bounding_box(), Some(Rect::new(1.0, 0.0, 3.0, 3.0)));
+        assert_eq!(TransparencyMask::new(0, 0).bounding_box(), None);
+    }
+
+    #[test]
+    fn combine_needs_matching_sizes() {
+        let a = TransparencyMask::filled(2, 2);
+        let b = TransparencyMask::new(2, 3);
+        assert_eq!(a.and(&b), None);
+        assert_eq!(a.or(&b), None);
+        assert_eq!(a.xor(&b), None);
+        assert_eq!(a.and(&TransparencyMask::new(3, 2)), None);
+    }
+
+    #[test]
+    fn combine_bitwise() {
+        let mut a = TransparencyMask::new(3, 1);
+        a.set(0, 0, true);
+        a.set(1, 0, true);
+        let mut b = TransparencyMask::new(3, 1);
+        b.set(1, 0, true);
+        b.set(2, 0, true);
+        let rows = |mask: TransparencyMask| mask.iter_rows().next().unwrap();
+        assert_eq!(rows(a.and(&b).unwrap()), vec![false, true, false]);
+        assert_eq!(rows(a.or(&b).unwrap()), vec![true, true, true]);
+        assert_eq!(rows(a.xor(&b).unwrap()), vec![true, false, true]);
+        // Alpha masks are combined through their threshold
+        let alpha = TransparencyMask::from_rgba_with(&rgba(&[200, 10, 200]), 3, 1, MaskSettings { alpha_threshold: 100, mode: MaskMode::Alpha });
+        assert_eq!(rows(a.and(&alpha).unwrap()), vec![true, false, false]);
+    }
+
+    #[test]
+    fn alpha_mode_applies_threshold_when_queried() {
+        let settings = MaskSettings { alpha_threshold: 128, mode: MaskMode::Alpha };
+        let mask = TransparencyMask::from_rgba_with(&rgba(&[0, 128, 129, 255]), 2, 2, settings);
+        assert_eq!(mask.mode(), MaskMode::Alpha);
+        assert_eq!(mask.settings(), settings);
+        assert_eq!(mask.iter_rows().collect::<Vec<_>>(), vec![vec![false, false], vec![true, true]]);
+        assert_eq!(mask.alpha(1, 0), 128);
+        assert_eq!(mask.as_bytes(), &[0, 128, 129, 255]);
+    }
+
+    #[test]
+    fn bitmask_applies_threshold_when_built() {
+        let mask = TransparencyMask::from_rgba_with(&rgba(&[0, 128, 129, 255]), 4, 1, MaskSettings::threshold(128));
+        assert_eq!(mask.iter_rows().next().unwrap(), vec![false, false, true, true]);
+        assert_eq!(mask.alpha(2, 0), 255);
+        assert_eq!(mask.settings(), MaskSettings::threshold(128));
+        // The default threshold treats any alpha above zero as opaque
+        assert_eq!(TransparencyMask::from_rgba(&rgba(&[0, 1]), 2, 1).count_opaque(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs 16 bytes")]
+    fn short_buffer_panics_in_bitmask_mode() {
+        TransparencyMask::from_rgba(&rgba(&[255, 255, 255]), 2, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs 16 bytes")]
+    fn short_buffer_panics_in_alpha_mode() {
+        let settings = MaskSettings { alpha_threshold: 0, mode: MaskMode::Alpha };
+        TransparencyMask::from_rgba_with(&rgba(&[255, 255, 255]), 2, 2, settings);
+    }
+}
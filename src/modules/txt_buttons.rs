@@ -87,6 +87,7 @@ pub struct TextButton {
 }
 
 impl TextButton {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(x: f32, y: f32, width: f32, height: f32, text: impl Into<String>, normal_color: Color, hover_color: Color, font_size: u16) -> Self {
         let enabled = true;
         let off_color = lerp_color(normal_color, GRAY, 0.5);